@@ -1,6 +1,11 @@
 use crate::MarinadeError;
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::stake::state::StakeState;
+use anchor_lang::solana_program::{
+    stake::state::{Delegation, StakeState},
+    stake_history::StakeHistoryEntry,
+    sysvar::stake_history,
+    vote,
+};
 use anchor_spl::token::{Mint, TokenAccount};
 
 pub fn check_owner_program<'info, A: ToAccountInfo<'info>>(
@@ -157,3 +162,495 @@ pub fn check_token_source_account<'info>(
     }
     Ok(())
 }
+
+// the vote account data starts with the u32 version tag followed by the node_pubkey (validator identity)
+// read it by offset because there is no CPU budget to deserialize the whole vote state
+pub fn get_vote_account_node_pubkey(vote_account: &AccountInfo) -> Result<Pubkey> {
+    check_owner_program(vote_account, &vote::program::ID, "validator_vote")?;
+    let data = vote_account.data.borrow();
+    if data.len() < 36 {
+        return err!(MarinadeError::InvalidVoteAccount);
+    }
+    Ok(Pubkey::new_from_array(data[4..36].try_into().unwrap()))
+}
+
+// only the current vote state versions are supported: 1 = V1_14_11, 2 = Current
+fn check_vote_account_version(data: &[u8]) -> Result<u32> {
+    if data.len() < 77 {
+        return err!(MarinadeError::InvalidVoteAccount);
+    }
+    let version = u32::from_le_bytes(data[0..4].try_into().unwrap());
+    if version != 1 && version != 2 {
+        msg!("Unsupported vote account version {}", version);
+        return err!(MarinadeError::InvalidVoteAccount);
+    }
+    Ok(version)
+}
+
+// commission (%) follows node_pubkey and authorized_withdrawer
+pub fn get_vote_account_commission(vote_account: &AccountInfo) -> Result<u8> {
+    check_owner_program(vote_account, &vote::program::ID, "validator_vote")?;
+    let data = vote_account.data.borrow();
+    check_vote_account_version(&data)?;
+    Ok(data[68])
+}
+
+// the votes queue follows the commission, the last vote is the latest voted slot
+// returns None if the validator has no votes
+pub fn get_vote_account_last_vote_slot(vote_account: &AccountInfo) -> Result<Option<u64>> {
+    check_owner_program(vote_account, &vote::program::ID, "validator_vote")?;
+    let data = vote_account.data.borrow();
+    // V1_14_11 votes are Lockout {slot, confirmation_count}, Current are LandedVote {latency, Lockout}
+    let (vote_size, slot_offset) = if check_vote_account_version(&data)? == 1 {
+        (12, 0)
+    } else {
+        (13, 1)
+    };
+    let votes_count = u64::from_le_bytes(data[69..77].try_into().unwrap()) as usize;
+    if votes_count == 0 {
+        return Ok(None);
+    }
+    let start = 77 + (votes_count - 1) * vote_size + slot_offset;
+    if data.len() < start + 8 {
+        return err!(MarinadeError::InvalidVoteAccount);
+    }
+    Ok(Some(u64::from_le_bytes(
+        data[start..start + 8].try_into().unwrap(),
+    )))
+}
+
+// the stake history sysvar is a Vec<(Epoch, StakeHistoryEntry)> sorted by epoch descending.
+// The entry is found by a binary search on the account data instead of deserializing the whole vector
+pub fn get_stake_history_entry(
+    stake_history: &AccountInfo,
+    epoch: u64,
+) -> Result<Option<StakeHistoryEntry>> {
+    require_keys_eq!(
+        *stake_history.key,
+        stake_history::ID,
+        MarinadeError::InvalidStakeHistory
+    );
+    const ENTRY_SIZE: usize = 32;
+    let data = stake_history.data.borrow();
+    let read_u64 = |start: usize| u64::from_le_bytes(data[start..start + 8].try_into().unwrap());
+    let len = read_u64(0) as usize;
+    if data.len() < 8 + len * ENTRY_SIZE {
+        return err!(MarinadeError::InvalidStakeHistory);
+    }
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let middle = (low + high) / 2;
+        let start = 8 + middle * ENTRY_SIZE;
+        let entry_epoch = read_u64(start);
+        if entry_epoch == epoch {
+            return Ok(Some(StakeHistoryEntry {
+                effective: read_u64(start + 8),
+                activating: read_u64(start + 16),
+                deactivating: read_u64(start + 24),
+            }));
+        } else if entry_epoch > epoch {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    Ok(None)
+}
+
+pub const DEFAULT_WARMUP_COOLDOWN_RATE: f64 = 0.25;
+pub const NEW_WARMUP_COOLDOWN_RATE: f64 = 0.09;
+
+// share of the cluster effective stake that can warm up or cool down in current_epoch.
+// delegation.warmup_cooldown_rate is deprecated, the stake program uses this since
+// the reduce_stake_warmup_cooldown feature
+pub fn warmup_cooldown_rate(current_epoch: u64, new_rate_epoch: u64) -> f64 {
+    if current_epoch < new_rate_epoch {
+        DEFAULT_WARMUP_COOLDOWN_RATE
+    } else {
+        NEW_WARMUP_COOLDOWN_RATE
+    }
+}
+
+// (effective, activating) stake of the delegation at target_epoch ignoring deactivation,
+// the same warmup computation as the stake program does
+fn get_stake_effective_and_activating_ignoring_deactivation(
+    delegation: &Delegation,
+    target_epoch: u64,
+    stake_history: &AccountInfo,
+    new_rate_epoch: u64,
+) -> Result<(u64, u64)> {
+    let delegated_stake = delegation.stake;
+    if delegation.activation_epoch == std::u64::MAX {
+        // bootstrap stake is fully effective
+        return Ok((delegated_stake, 0));
+    }
+    if delegation.activation_epoch == delegation.deactivation_epoch {
+        return Ok((0, 0));
+    }
+    if target_epoch == delegation.activation_epoch {
+        return Ok((0, delegated_stake));
+    }
+    if target_epoch < delegation.activation_epoch {
+        return Ok((0, 0));
+    }
+    let mut prev_epoch = delegation.activation_epoch;
+    let mut prev_cluster_stake =
+        if let Some(entry) = get_stake_history_entry(stake_history, prev_epoch)? {
+            entry
+        } else {
+            // out of the history, fully effective
+            return Ok((delegated_stake, 0));
+        };
+    let mut effective_stake = 0;
+    loop {
+        let current_epoch = prev_epoch + 1;
+        if prev_cluster_stake.activating == 0 {
+            // no activating stake in the cluster, all is effective
+            break;
+        }
+        let weight =
+            (delegated_stake - effective_stake) as f64 / prev_cluster_stake.activating as f64;
+        let newly_effective_cluster_stake = prev_cluster_stake.effective as f64
+            * warmup_cooldown_rate(current_epoch, new_rate_epoch);
+        effective_stake += ((weight * newly_effective_cluster_stake) as u64).max(1);
+        if effective_stake >= delegated_stake {
+            effective_stake = delegated_stake;
+            break;
+        }
+        if current_epoch >= target_epoch || current_epoch >= delegation.deactivation_epoch {
+            break;
+        }
+        if let Some(entry) = get_stake_history_entry(stake_history, current_epoch)? {
+            prev_epoch = current_epoch;
+            prev_cluster_stake = entry;
+        } else {
+            break;
+        }
+    }
+    Ok((effective_stake, delegated_stake - effective_stake))
+}
+
+/// (effective, activating) stake of a delegation not yet deactivated at target_epoch
+pub fn get_stake_effective_and_activating(
+    delegation: &Delegation,
+    target_epoch: u64,
+    stake_history: &AccountInfo,
+    new_rate_epoch: u64,
+) -> Result<(u64, u64)> {
+    require_gt!(
+        delegation.deactivation_epoch,
+        target_epoch,
+        MarinadeError::RequiredActiveStake
+    );
+    get_stake_effective_and_activating_ignoring_deactivation(
+        delegation,
+        target_epoch,
+        stake_history,
+        new_rate_epoch,
+    )
+}
+
+/// effective stake still cooling down at target_epoch, 0 when the stake is inactive
+pub fn get_stake_effective_deactivating(
+    delegation: &Delegation,
+    target_epoch: u64,
+    stake_history: &AccountInfo,
+    new_rate_epoch: u64,
+) -> Result<u64> {
+    let (effective_stake, _) = get_stake_effective_and_activating_ignoring_deactivation(
+        delegation,
+        target_epoch.min(delegation.deactivation_epoch),
+        stake_history,
+        new_rate_epoch,
+    )?;
+    if target_epoch <= delegation.deactivation_epoch {
+        return Ok(effective_stake);
+    }
+    let mut prev_epoch = delegation.deactivation_epoch;
+    let mut prev_cluster_stake =
+        if let Some(entry) = get_stake_history_entry(stake_history, prev_epoch)? {
+            entry
+        } else {
+            // out of the history, fully deactivated
+            return Ok(0);
+        };
+    let mut effective_stake = effective_stake;
+    loop {
+        let current_epoch = prev_epoch + 1;
+        if prev_cluster_stake.deactivating == 0 {
+            // no deactivating stake in the cluster, the stake program stops here too
+            break;
+        }
+        let weight = effective_stake as f64 / prev_cluster_stake.deactivating as f64;
+        let newly_not_effective_cluster_stake = prev_cluster_stake.effective as f64
+            * warmup_cooldown_rate(current_epoch, new_rate_epoch);
+        effective_stake = effective_stake
+            .saturating_sub(((weight * newly_not_effective_cluster_stake) as u64).max(1));
+        if effective_stake == 0 || current_epoch >= target_epoch {
+            break;
+        }
+        if let Some(entry) = get_stake_history_entry(stake_history, current_epoch)? {
+            prev_epoch = current_epoch;
+            prev_cluster_stake = entry;
+        } else {
+            break;
+        }
+    }
+    Ok(effective_stake)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::{
+        stake_history::StakeHistory,
+        sysvar::Sysvar,
+        vote::state::{VoteInit, VoteState, VoteStateVersions},
+    };
+
+    fn stake_history_data(stake_history: &StakeHistory) -> Vec<u8> {
+        let key = stake_history::ID;
+        let mut lamports = 0;
+        let mut data = vec![0; StakeHistory::size_of()];
+        let mut account_info =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &key, false, 0);
+        stake_history.to_account_info(&mut account_info).unwrap();
+        data
+    }
+
+    // cluster with a lot of stake activating and deactivating, so a stake takes several epochs
+    fn test_stake_history() -> StakeHistory {
+        let mut stake_history = StakeHistory::default();
+        for epoch in 0..30 {
+            stake_history.add(
+                epoch,
+                StakeHistoryEntry {
+                    effective: 1_000_000_000 + epoch * 1_000_000,
+                    activating: 5_000_000_000 - epoch * 10_000_000,
+                    deactivating: 4_000_000_000 + epoch * 10_000_000,
+                },
+            );
+        }
+        stake_history
+    }
+
+    #[test]
+    fn test_get_stake_history_entry() -> Result<()> {
+        let stake_history = test_stake_history();
+        let key = stake_history::ID;
+        let mut lamports = 0;
+        let mut data = stake_history_data(&stake_history);
+        let account_info =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &key, false, 0);
+        for epoch in 0..30 {
+            assert_eq!(
+                get_stake_history_entry(&account_info, epoch)?.as_ref(),
+                stake_history.get(epoch)
+            );
+        }
+        assert_eq!(get_stake_history_entry(&account_info, 30)?, None);
+
+        let wrong_key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = stake_history_data(&stake_history);
+        let account_info = AccountInfo::new(
+            &wrong_key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &key,
+            false,
+            0,
+        );
+        assert!(get_stake_history_entry(&account_info, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_stake_effective_matches_stake_program() -> Result<()> {
+        let stake_history = test_stake_history();
+        let key = stake_history::ID;
+        let mut lamports = 0;
+        let mut data = stake_history_data(&stake_history);
+        let account_info =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &key, false, 0);
+        let mut delegation = Delegation::new(
+            &Pubkey::new_unique(),
+            40_000_000,
+            3,
+            DEFAULT_WARMUP_COOLDOWN_RATE,
+        );
+        delegation.deactivation_epoch = 12;
+        // the stake program of this solana-program version always uses the old rate
+        let new_rate_epoch = std::u64::MAX;
+        let mut was_activating = false;
+        let mut was_deactivating = false;
+        for target_epoch in 0..30 {
+            let status =
+                delegation.stake_activating_and_deactivating(target_epoch, Some(&stake_history));
+            if target_epoch < delegation.deactivation_epoch {
+                assert_eq!(
+                    get_stake_effective_and_activating(
+                        &delegation,
+                        target_epoch,
+                        &account_info,
+                        new_rate_epoch
+                    )?,
+                    (status.effective, status.activating),
+                    "epoch {}",
+                    target_epoch
+                );
+                was_activating |= status.effective > 0 && status.activating > 0;
+            } else {
+                assert_eq!(
+                    get_stake_effective_deactivating(
+                        &delegation,
+                        target_epoch,
+                        &account_info,
+                        new_rate_epoch
+                    )?,
+                    status.deactivating,
+                    "epoch {}",
+                    target_epoch
+                );
+                was_deactivating |=
+                    target_epoch > delegation.deactivation_epoch && status.deactivating > 0;
+            }
+        }
+        // the history really spreads the stake over several epochs
+        assert!(was_activating);
+        assert!(was_deactivating);
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_warmup_cooldown_rate() -> Result<()> {
+        assert_eq!(warmup_cooldown_rate(9, 10), DEFAULT_WARMUP_COOLDOWN_RATE);
+        assert_eq!(warmup_cooldown_rate(10, 10), NEW_WARMUP_COOLDOWN_RATE);
+
+        let mut stake_history = StakeHistory::default();
+        stake_history.add(
+            3,
+            StakeHistoryEntry {
+                effective: 1_000_000_000,
+                activating: 5_000_000_000,
+                deactivating: 0,
+            },
+        );
+        let key = stake_history::ID;
+        let mut lamports = 0;
+        let mut data = stake_history_data(&stake_history);
+        let account_info =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &key, false, 0);
+        let delegation = Delegation::new(
+            &Pubkey::new_unique(),
+            40_000_000,
+            3,
+            DEFAULT_WARMUP_COOLDOWN_RATE,
+        );
+        // 40M / 5000M of 1000M * rate become effective in epoch 4
+        assert_eq!(
+            get_stake_effective_and_activating(&delegation, 4, &account_info, 0)?,
+            (720_000, 39_280_000)
+        );
+        assert_eq!(
+            get_stake_effective_and_activating(&delegation, 4, &account_info, 5)?,
+            (2_000_000, 38_000_000)
+        );
+        Ok(())
+    }
+
+    fn vote_account_data(vote_state: VoteState) -> Vec<u8> {
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(&VoteStateVersions::new_current(vote_state), &mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_get_vote_account_fields() -> Result<()> {
+        let node_pubkey = Pubkey::new_unique();
+        let mut vote_state = VoteState::new(
+            &VoteInit {
+                node_pubkey,
+                authorized_voter: Pubkey::new_unique(),
+                authorized_withdrawer: Pubkey::new_unique(),
+                commission: 7,
+            },
+            &Clock::default(),
+        );
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vote_account_data(vote_state.clone());
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &vote::program::ID,
+            false,
+            0,
+        );
+        assert_eq!(get_vote_account_node_pubkey(&account_info)?, node_pubkey);
+        assert_eq!(get_vote_account_commission(&account_info)?, 7);
+        assert_eq!(get_vote_account_last_vote_slot(&account_info)?, None);
+
+        for slot in 100..140 {
+            vote_state.process_next_vote_slot(slot, 0);
+        }
+        let mut lamports = 0;
+        let mut data = vote_account_data(vote_state.clone());
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &vote::program::ID,
+            false,
+            0,
+        );
+        assert_eq!(get_vote_account_last_vote_slot(&account_info)?, Some(139));
+
+        // the same state in the Current layout where each vote is prefixed by its latency
+        let votes_count = vote_state.votes.len();
+        let mut current_data = 2u32.to_le_bytes().to_vec();
+        current_data.extend_from_slice(&data[4..77]);
+        for vote in 0..votes_count {
+            current_data.push(0);
+            current_data.extend_from_slice(&data[77 + vote * 12..77 + (vote + 1) * 12]);
+        }
+        current_data.extend_from_slice(&data[77 + votes_count * 12..]);
+        let mut lamports = 0;
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut current_data,
+            &vote::program::ID,
+            false,
+            0,
+        );
+        assert_eq!(get_vote_account_commission(&account_info)?, 7);
+        assert_eq!(get_vote_account_last_vote_slot(&account_info)?, Some(139));
+
+        // not a vote account
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vote_account_data(vote_state);
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert!(get_vote_account_commission(&account_info).is_err());
+        Ok(())
+    }
+}
@@ -288,4 +288,78 @@ pub enum MarinadeError {
 
     #[msg("Deposit SOL fee is too high")]
     DepositSolFeeIsTooHigh, // 6092 0x17cc
+
+    #[msg("Unlisted stake deposit is not enabled")]
+    UnlistedStakeDepositIsNotEnabled, // 6093 0x17cd
+
+    #[msg("Unlisted stake deposit fee is too high")]
+    UnlistedStakeDepositFeeIsTooHigh, // 6094 0x17ce
+
+    #[msg("Depositing stake of a listed validator as unlisted")]
+    DepositingListedValidatorStake, // 6095 0x17cf
+
+    #[msg("Permissionless add validator is not enabled")]
+    PermissionlessAddValidatorIsNotEnabled, // 6096 0x17d0
+
+    #[msg("Validator bond is too low")]
+    ValidatorBondIsTooLow, // 6097 0x17d1
+
+    #[msg("Invalid vote account")]
+    InvalidVoteAccount, // 6098 0x17d2
+
+    #[msg("Wrong validator identity")]
+    WrongValidatorIdentity, // 6099 0x17d3
+
+    #[msg("Validator is still in the validator list")]
+    ValidatorIsStillListed, // 6100 0x17d4
+
+    #[msg("Slashing more than the validator bond")]
+    SlashingMoreThanValidatorBond, // 6101 0x17d5
+
+    #[msg("Expected epoch reward rate is too high")]
+    ExpectedEpochRewardRateIsTooHigh, // 6102 0x17d6
+
+    #[msg("Max validator commission is too high")]
+    MaxValidatorCommissionIsTooHigh, // 6103 0x17d7
+
+    #[msg("Pending validator scores are not promoted")]
+    PendingScoresNotPromoted, // 6104 0x17d8
+
+    #[msg("Validator is already migrated to another vote account")]
+    ValidatorAlreadyMigrated, // 6105 0x17d9
+
+    #[msg("A validator with a lower score is over target, unstake from it first")]
+    LowerScoreValidatorOverTarget, // 6106 0x17da
+
+    #[msg("Batch items do not match the accounts")]
+    BatchAccountsMismatch, // 6107 0x17db
+
+    #[msg("Invalid stake history account")]
+    InvalidStakeHistory, // 6108 0x17dc
+    #[msg("Stake account is still cooling down")]
+    StakeStillCoolingDown, // 6109 0x17dd
+
+    #[msg("Stake accounts can not be merged")]
+    IncompatibleStakesToMerge, // 6110 0x17de
+
+    #[msg("Max stake account size is lower than min stake")]
+    MaxStakeAccountSizeIsTooLow, // 6111 0x17df
+
+    #[msg("Validator is not the most under-target validator of the page")]
+    NotMostUnderTargetValidator, // 6112 0x17e0
+
+    #[msg("Unlisted stake deposit fee is lower than the deposit stake account fee")]
+    UnlistedStakeDepositFeeIsTooLow, // 6113 0x17e1
+
+    #[msg("Validator bond withdrawal is not requested or the delay is not over")]
+    ValidatorBondIsLocked, // 6114 0x17e2
+
+    #[msg("Validator bond withdrawal is already requested")]
+    ValidatorBondWithdrawalAlreadyRequested, // 6115 0x17e3
+
+    #[msg("Validator record size is too small, run resize_validator_records")]
+    ValidatorRecordSizeIsTooSmall, // 6116 0x17e4
+
+    #[msg("Native redelegate is not enabled")]
+    NativeRedelegateIsNotEnabled, // 6117 0x17e5
 }
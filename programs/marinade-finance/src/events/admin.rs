@@ -4,6 +4,7 @@ use crate::instructions::InitializeData;
 
 use super::{
     BoolValueChange, FeeCentsValueChange, FeeValueChange, PubkeyValueChange, U64ValueChange,
+    U8ValueChange,
 };
 
 #[event]
@@ -41,6 +42,21 @@ pub struct ConfigMarinadeEvent {
     pub max_stake_moved_per_epoch_change: Option<FeeValueChange>,
     pub deposit_sol_fee_change: Option<FeeCentsValueChange>,
     pub deposit_stake_account_fee_change: Option<FeeCentsValueChange>,
+    pub unlisted_stake_deposit_enabled_change: Option<BoolValueChange>,
+    pub unlisted_stake_deposit_fee_change: Option<FeeCentsValueChange>,
+    pub permissionless_add_validator_enabled_change: Option<BoolValueChange>,
+    pub min_validator_bond_change: Option<U64ValueChange>,
+    pub expected_epoch_reward_rate_change: Option<FeeCentsValueChange>,
+    pub max_validator_stake_share_change: Option<FeeValueChange>,
+    pub max_validator_commission_change: Option<U8ValueChange>,
+    pub max_delinquent_slots_change: Option<U64ValueChange>,
+    pub stake_target_hysteresis_change: Option<FeeValueChange>,
+    pub unstake_lowest_score_first_change: Option<BoolValueChange>,
+    pub max_stake_account_size_change: Option<U64ValueChange>,
+    pub seconds_for_stake_delta_change: Option<U64ValueChange>,
+    pub early_stake_delta_threshold_change: Option<U64ValueChange>,
+    pub native_redelegate_enabled_change: Option<BoolValueChange>,
+    pub new_warmup_cooldown_rate_epoch_change: Option<U64ValueChange>,
 }
 
 // TODO: ConfigValidatorSystemEvent?
@@ -75,9 +91,25 @@ pub struct ReallocValidatorListEvent {
     pub new_capacity: u32,
 }
 
+#[event]
+pub struct ResizeValidatorRecordsEvent {
+    pub state: Pubkey,
+    pub count: u32,
+    pub old_record_size: u32,
+    pub new_record_size: u32,
+}
+
 #[event]
 pub struct ReallocStakeListEvent {
     pub state: Pubkey,
     pub count: u32,
     pub new_capacity: u32,
 }
+
+#[event]
+pub struct SlashValidatorBondEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub lamports: u64,
+    pub bond_balance: u64,
+}
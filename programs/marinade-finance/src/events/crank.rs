@@ -1,8 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::state::Fee;
+use crate::state::{stake_system::StakeStatus, Fee};
 
-use super::U64ValueChange;
+use super::{BoolValueChange, U32ValueChange, U64ValueChange};
 
 #[derive(Clone, AnchorDeserialize, AnchorSerialize)]
 pub struct SplitStakeAccountInfo {
@@ -63,29 +63,6 @@ pub struct CreateCanonicalStakeEvent {
     pub operational_sol_balance: u64,
 }
 
-#[event]
-pub struct RedelegateEvent {
-    pub state: Pubkey,
-    pub epoch: u64,
-    pub stake_index: u32,
-    pub stake_account: Pubkey,
-    pub last_update_delegation: u64,
-    pub source_validator_index: u32,
-    pub source_validator_vote: Pubkey,
-    pub source_validator_score: u32,
-    pub source_validator_balance: u64,
-    pub source_validator_stake_target: u64,
-    pub dest_validator_index: u32,
-    pub dest_validator_vote: Pubkey,
-    pub dest_validator_score: u32,
-    pub dest_validator_balance: u64,
-    pub dest_validator_stake_target: u64,
-    pub redelegate_amount: u64,
-    pub split_stake_account: Option<SplitStakeAccountInfo>, // None if whole stake is being redelegated
-    pub redelegate_stake_index: u32,
-    pub redelegate_stake_account: Pubkey,
-}
-
 #[event]
 pub struct StakeReserveEvent {
     pub state: Pubkey,
@@ -124,6 +101,24 @@ pub struct UpdateActiveEvent {
     pub msol_supply: u64,
 }
 
+// the UpdateActiveEvent of each stake in the batch has no msol fees and no mSOL price change,
+// the fees of the whole batch are minted once and the price is updated once at the end
+#[event]
+pub struct UpdateActiveBatchEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub stakes_count: u32,
+    // rewards and extra lamports of all the stakes, the protocol fee is taken from it
+    pub fee_lamports_incoming: u64,
+    pub msol_fees: Option<u64>,
+    pub total_active_balance: u64,
+    pub msol_price_change: U64ValueChange,
+    pub reward_fee_used: Fee,
+    // MSOL price used
+    pub total_virtual_staked_lamports: u64,
+    pub msol_supply: u64,
+}
+
 #[event]
 pub struct UpdateDeactivatedEvent {
     pub state: Pubkey,
@@ -140,3 +135,101 @@ pub struct UpdateDeactivatedEvent {
     pub total_virtual_staked_lamports: u64,
     pub msol_supply: u64,
 }
+
+#[event]
+pub struct BondSettlementEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub validator_index: u32,
+    pub validator_vote: Pubkey,
+    pub rewards: u64,
+    pub extra_lamports: u64,
+    pub expected_rewards: u64,
+    pub shortfall: u64,
+    pub settled_lamports: u64,
+    pub bond_balance: u64,
+}
+
+#[event]
+pub struct CheckValidatorStakeCapEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub index: u32,
+    pub stake_cap: u64,
+    pub total_stake_target: u64,
+    pub capped_stake_change: U64ValueChange,
+    pub total_capped_stake: u64,
+}
+
+#[event]
+pub struct CheckValidatorCommissionEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub index: u32,
+    pub commission: u8,
+    pub max_commission: u8,
+    pub commission_exceeded_change: BoolValueChange,
+}
+
+#[event]
+pub struct CheckValidatorDelinquencyEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub index: u32,
+    pub last_vote_slot: Option<u64>,
+    pub delinquent_slots: u64,
+    pub max_delinquent_slots: u64,
+    pub delinquent_change: BoolValueChange,
+}
+
+#[event]
+pub struct PromoteValidatorScoresEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub promoted_count: u32,
+    pub pending_scores_count: u32,
+    pub total_validator_score_change: U32ValueChange,
+}
+
+#[event]
+pub struct ReconcileDeactivatedStakeEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub stake_index: u32,
+    pub stake_account: Pubkey,
+    pub validator_index: u32,
+    pub validator_vote: Pubkey,
+    pub deactivation_epoch: u64,
+    pub amount: u64,
+    pub validator_active_balance: u64,
+    pub total_active_balance: u64,
+    pub emergency_cooling_down: u64,
+}
+
+#[event]
+pub struct MergeStakeAccountsEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub status: StakeStatus,
+    pub destination_stake_index: u32,
+    pub destination_stake_account: Pubkey,
+    pub last_update_destination_stake_delegation: u64,
+    pub source_stake_index: u32,
+    pub source_stake_account: Pubkey,
+    pub last_update_source_stake_delegation: u64,
+    pub validator_vote: Pubkey,
+    pub extra_delegated: u64,
+    pub returned_stake_rent: u64,
+    pub total_active_balance: u64,
+    pub operational_sol_balance: u64,
+}
+
+#[event]
+pub struct GcStakeListEvent {
+    pub state: Pubkey,
+    pub start_index: u32,
+    pub scanned_count: u32,
+    pub removed_stake_accounts: Vec<Pubkey>,
+    pub reclaimed_rent: u64,
+    pub stake_count: u32,
+}
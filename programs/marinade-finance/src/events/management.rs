@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use super::U32ValueChange;
+use super::{crank::SplitStakeAccountInfo, U32ValueChange, U64ValueChange};
 
 #[event]
 pub struct AddValidatorEvent {
@@ -28,3 +28,127 @@ pub struct SetValidatorScoreEvent {
     pub index: u32,
     pub score_change: U32ValueChange,
 }
+
+#[event]
+pub struct RegisterValidatorEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub index: u32,
+    pub identity: Pubkey,
+    pub bond_lamports: u64,
+}
+
+#[event]
+pub struct WithdrawValidatorBondEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub authority: Pubkey,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct RequestValidatorBondWithdrawalEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub authority: Pubkey,
+    pub epoch: u64,
+    pub withdrawable_epoch: u64,
+}
+
+#[event]
+pub struct InitValidatorBondEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub authority: Pubkey,
+    pub bond_lamports: u64,
+}
+
+#[event]
+pub struct SetValidatorStakeCapEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub index: u32,
+    pub stake_cap_change: U64ValueChange,
+}
+
+#[event]
+pub struct SetValidatorScoresEvent {
+    pub state: Pubkey,
+    pub updated_count: u32,
+    pub total_validator_score_change: U32ValueChange,
+}
+
+#[event]
+pub struct ScheduleValidatorScoresEvent {
+    pub state: Pubkey,
+    pub effective_epoch: u64,
+    pub scheduled_count: u32,
+    pub pending_scores_count: u32,
+}
+
+#[event]
+pub struct MarkValidatorForRemovalEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub index: u32,
+    pub active_balance: u64,
+}
+
+#[event]
+pub struct MigrateValidatorEvent {
+    pub state: Pubkey,
+    pub old_validator: Pubkey,
+    pub old_index: u32,
+    pub new_validator: Pubkey,
+    pub new_index: u32,
+    pub score: u32,
+}
+
+#[event]
+pub struct EmergencyUnstakeValidatorEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub index: u32,
+    pub authority: Pubkey,
+    pub is_pause_authority: bool,
+    pub unstaked_accounts: Vec<Pubkey>,
+    pub total_unstake_amount: u64,
+    pub active_balance_left: u64,
+    pub emergency_cooling_down: u64,
+}
+
+#[event]
+pub struct RedelegateEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub stake_index: u32,
+    pub stake_account: Pubkey,
+    pub last_update_delegation: u64,
+    pub source_validator_index: u32,
+    pub source_validator_vote: Pubkey,
+    pub source_validator_score: u32,
+    pub source_validator_balance: u64,
+    pub source_validator_stake_target: u64,
+    pub dest_validator_index: u32,
+    pub dest_validator_vote: Pubkey,
+    pub dest_validator_score: u32,
+    pub dest_validator_balance: u64,
+    pub dest_validator_stake_target: u64,
+    pub redelegate_amount: u64,
+    pub split_stake_account: Option<SplitStakeAccountInfo>, // None if whole stake is being redelegated
+    pub redelegate_stake_index: u32,
+    pub redelegate_stake_account: Pubkey,
+}
+
+#[event]
+pub struct SplitOversizedStakeEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub stake_index: u32,
+    pub stake_account: Pubkey,
+    pub split_stake_index: u32,
+    pub split_stake_account: Pubkey,
+    pub validator_vote: Pubkey,
+    pub split_amount: u64,
+    pub remaining_amount: u64,
+}
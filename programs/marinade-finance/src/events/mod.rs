@@ -21,6 +21,12 @@ pub struct U32ValueChange {
     pub new: u32,
 }
 
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct U8ValueChange {
+    pub old: u8,
+    pub new: u8,
+}
+
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
 pub struct FeeValueChange {
     pub old: Fee,
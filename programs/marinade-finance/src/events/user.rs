@@ -19,6 +19,23 @@ pub struct DepositStakeAccountEvent {
     pub sol_fees: u64,
 }
 
+#[event]
+pub struct DepositUnlistedStakeAccountEvent {
+    pub state: Pubkey,
+    pub stake: Pubkey,
+    pub delegated: u64,     // lamports in the stake account delegation
+    pub withdrawer: Pubkey, // withdraw auth for the stake account
+    pub stake_index: u32,
+    pub validator: Pubkey,
+    pub emergency_cooling_down: u64,
+    pub user_msol_balance: u64,
+    pub msol_minted: u64,
+    // MSOL price used
+    pub total_virtual_staked_lamports: u64,
+    pub msol_supply: u64,
+    pub sol_fees: u64,
+}
+
 #[event]
 pub struct DepositEvent {
     pub state: Pubkey,
@@ -1,6 +1,6 @@
 use crate::events::{
     admin::ConfigMarinadeEvent, BoolValueChange, FeeCentsValueChange, FeeValueChange,
-    U64ValueChange,
+    U64ValueChange, U8ValueChange,
 };
 use crate::{
     require_lte,
@@ -24,6 +24,21 @@ pub struct ConfigMarinadeParams {
     pub max_stake_moved_per_epoch: Option<Fee>,
     pub deposit_sol_fee: Option<FeeCents>,
     pub deposit_stake_account_fee: Option<FeeCents>,
+    pub unlisted_stake_deposit_enabled: Option<bool>,
+    pub unlisted_stake_deposit_fee: Option<FeeCents>,
+    pub permissionless_add_validator_enabled: Option<bool>,
+    pub min_validator_bond: Option<u64>,
+    pub expected_epoch_reward_rate: Option<FeeCents>,
+    pub max_validator_stake_share: Option<Fee>,
+    pub max_validator_commission: Option<u8>,
+    pub max_delinquent_slots: Option<u64>,
+    pub stake_target_hysteresis: Option<Fee>,
+    pub unstake_lowest_score_first: Option<bool>,
+    pub max_stake_account_size: Option<u64>,
+    pub seconds_for_stake_delta: Option<u64>,
+    pub early_stake_delta_threshold: Option<u64>,
+    pub native_redelegate_enabled: Option<bool>,
+    pub new_warmup_cooldown_rate_epoch: Option<u64>,
 }
 
 #[derive(Accounts)]
@@ -54,6 +69,21 @@ impl<'info> ConfigMarinade<'info> {
             max_stake_moved_per_epoch,
             deposit_sol_fee,
             deposit_stake_account_fee,
+            unlisted_stake_deposit_enabled,
+            unlisted_stake_deposit_fee,
+            permissionless_add_validator_enabled,
+            min_validator_bond,
+            expected_epoch_reward_rate,
+            max_validator_stake_share,
+            max_validator_commission,
+            max_delinquent_slots,
+            stake_target_hysteresis,
+            unstake_lowest_score_first,
+            max_stake_account_size,
+            seconds_for_stake_delta,
+            early_stake_delta_threshold,
+            native_redelegate_enabled,
+            new_warmup_cooldown_rate_epoch,
         }: ConfigMarinadeParams,
     ) -> Result<()> {
         let rewards_fee_change = if let Some(rewards_fee) = rewards_fee {
@@ -235,6 +265,220 @@ impl<'info> ConfigMarinade<'info> {
                 None
             };
 
+        let unlisted_stake_deposit_enabled_change =
+            if let Some(unlisted_stake_deposit_enabled) = unlisted_stake_deposit_enabled {
+                let old = self.state.unlisted_stake_deposit_enabled;
+                self.state.unlisted_stake_deposit_enabled = unlisted_stake_deposit_enabled;
+                Some(BoolValueChange {
+                    old,
+                    new: unlisted_stake_deposit_enabled,
+                })
+            } else {
+                None
+            };
+
+        let unlisted_stake_deposit_fee_change =
+            if let Some(unlisted_stake_deposit_fee) = unlisted_stake_deposit_fee {
+                require_lte!(
+                    unlisted_stake_deposit_fee,
+                    State::MAX_UNLISTED_STAKE_DEPOSIT_FEE,
+                    MarinadeError::UnlistedStakeDepositFeeIsTooHigh
+                );
+                let old = self.state.unlisted_stake_deposit_fee;
+                self.state.unlisted_stake_deposit_fee = unlisted_stake_deposit_fee;
+                Some(FeeCentsValueChange {
+                    old,
+                    new: unlisted_stake_deposit_fee,
+                })
+            } else {
+                None
+            };
+        if self.state.unlisted_stake_deposit_enabled {
+            // unlisted stakes lose the rewards of the epoch they cool down in
+            require_gte!(
+                self.state.unlisted_stake_deposit_fee,
+                self.state.deposit_stake_account_fee,
+                MarinadeError::UnlistedStakeDepositFeeIsTooLow
+            );
+        }
+
+        let permissionless_add_validator_enabled_change =
+            if let Some(enabled) = permissionless_add_validator_enabled {
+                let old = self.state.permissionless_add_validator_enabled;
+                self.state.permissionless_add_validator_enabled = enabled;
+                Some(BoolValueChange { old, new: enabled })
+            } else {
+                None
+            };
+
+        let min_validator_bond_change = if let Some(min_validator_bond) = min_validator_bond {
+            let old = self.state.min_validator_bond;
+            self.state.min_validator_bond = min_validator_bond;
+            Some(U64ValueChange {
+                old,
+                new: min_validator_bond,
+            })
+        } else {
+            None
+        };
+
+        let expected_epoch_reward_rate_change =
+            if let Some(expected_epoch_reward_rate) = expected_epoch_reward_rate {
+                require_lte!(
+                    expected_epoch_reward_rate,
+                    State::MAX_EXPECTED_EPOCH_REWARD_RATE,
+                    MarinadeError::ExpectedEpochRewardRateIsTooHigh
+                );
+                let old = self.state.expected_epoch_reward_rate;
+                self.state.expected_epoch_reward_rate = expected_epoch_reward_rate;
+                Some(FeeCentsValueChange {
+                    old,
+                    new: expected_epoch_reward_rate,
+                })
+            } else {
+                None
+            };
+
+        let max_validator_stake_share_change =
+            if let Some(max_validator_stake_share) = max_validator_stake_share {
+                max_validator_stake_share.check()?;
+                let old = self.state.max_validator_stake_share;
+                self.state.max_validator_stake_share = max_validator_stake_share;
+                Some(FeeValueChange {
+                    old,
+                    new: max_validator_stake_share,
+                })
+            } else {
+                None
+            };
+
+        let max_validator_commission_change =
+            if let Some(max_validator_commission) = max_validator_commission {
+                require_lte!(
+                    max_validator_commission,
+                    100,
+                    MarinadeError::MaxValidatorCommissionIsTooHigh
+                );
+                let old = self.state.max_validator_commission;
+                self.state.max_validator_commission = max_validator_commission;
+                Some(U8ValueChange {
+                    old,
+                    new: max_validator_commission,
+                })
+            } else {
+                None
+            };
+
+        let max_delinquent_slots_change = if let Some(max_delinquent_slots) = max_delinquent_slots {
+            let old = self.state.max_delinquent_slots;
+            self.state.max_delinquent_slots = max_delinquent_slots;
+            Some(U64ValueChange {
+                old,
+                new: max_delinquent_slots,
+            })
+        } else {
+            None
+        };
+
+        let stake_target_hysteresis_change =
+            if let Some(stake_target_hysteresis) = stake_target_hysteresis {
+                stake_target_hysteresis.check()?;
+                let old = self.state.stake_target_hysteresis;
+                self.state.stake_target_hysteresis = stake_target_hysteresis;
+                Some(FeeValueChange {
+                    old,
+                    new: stake_target_hysteresis,
+                })
+            } else {
+                None
+            };
+
+        let unstake_lowest_score_first_change =
+            if let Some(unstake_lowest_score_first) = unstake_lowest_score_first {
+                let old = self.state.unstake_lowest_score_first;
+                self.state.unstake_lowest_score_first = unstake_lowest_score_first;
+                Some(BoolValueChange {
+                    old,
+                    new: unstake_lowest_score_first,
+                })
+            } else {
+                None
+            };
+
+        let max_stake_account_size_change =
+            if let Some(max_stake_account_size) = max_stake_account_size {
+                if max_stake_account_size > 0 {
+                    require_gte!(
+                        max_stake_account_size,
+                        self.state.stake_system.min_stake,
+                        MarinadeError::MaxStakeAccountSizeIsTooLow
+                    );
+                }
+                let old = self.state.max_stake_account_size;
+                self.state.max_stake_account_size = max_stake_account_size;
+                Some(U64ValueChange {
+                    old,
+                    new: max_stake_account_size,
+                })
+            } else {
+                None
+            };
+
+        let seconds_for_stake_delta_change =
+            if let Some(seconds_for_stake_delta) = seconds_for_stake_delta {
+                if seconds_for_stake_delta > 0 {
+                    require_gte!(
+                        seconds_for_stake_delta,
+                        State::MIN_SECONDS_FOR_STAKE_DELTA,
+                        MarinadeError::UpdateWindowIsTooLow
+                    );
+                }
+                let old = self.state.seconds_for_stake_delta;
+                self.state.seconds_for_stake_delta = seconds_for_stake_delta;
+                Some(U64ValueChange {
+                    old,
+                    new: seconds_for_stake_delta,
+                })
+            } else {
+                None
+            };
+
+        let early_stake_delta_threshold_change =
+            if let Some(early_stake_delta_threshold) = early_stake_delta_threshold {
+                let old = self.state.early_stake_delta_threshold;
+                self.state.early_stake_delta_threshold = early_stake_delta_threshold;
+                Some(U64ValueChange {
+                    old,
+                    new: early_stake_delta_threshold,
+                })
+            } else {
+                None
+            };
+
+        let native_redelegate_enabled_change =
+            if let Some(native_redelegate_enabled) = native_redelegate_enabled {
+                let old = self.state.native_redelegate_enabled;
+                self.state.native_redelegate_enabled = native_redelegate_enabled;
+                Some(BoolValueChange {
+                    old,
+                    new: native_redelegate_enabled,
+                })
+            } else {
+                None
+            };
+
+        let new_warmup_cooldown_rate_epoch_change =
+            if let Some(new_warmup_cooldown_rate_epoch) = new_warmup_cooldown_rate_epoch {
+                let old = self.state.new_warmup_cooldown_rate_epoch;
+                self.state.new_warmup_cooldown_rate_epoch = new_warmup_cooldown_rate_epoch;
+                Some(U64ValueChange {
+                    old,
+                    new: new_warmup_cooldown_rate_epoch,
+                })
+            } else {
+                None
+            };
+
         let max_stake_moved_per_epoch_change =
             if let Some(max_stake_moved_per_epoch) = max_stake_moved_per_epoch {
                 // Not checking for 100% because probably for some emergency case
@@ -266,6 +510,21 @@ impl<'info> ConfigMarinade<'info> {
             max_stake_moved_per_epoch_change,
             deposit_sol_fee_change,
             deposit_stake_account_fee_change,
+            unlisted_stake_deposit_enabled_change,
+            unlisted_stake_deposit_fee_change,
+            permissionless_add_validator_enabled_change,
+            min_validator_bond_change,
+            expected_epoch_reward_rate_change,
+            max_validator_stake_share_change,
+            max_validator_commission_change,
+            max_delinquent_slots_change,
+            stake_target_hysteresis_change,
+            unstake_lowest_score_first_change,
+            max_stake_account_size_change,
+            seconds_for_stake_delta_change,
+            early_stake_delta_threshold_change,
+            native_redelegate_enabled_change,
+            new_warmup_cooldown_rate_epoch_change,
         });
 
         Ok(())
@@ -186,11 +186,29 @@ impl<'info> Initialize<'info> {
             withdraw_stake_account_fee: FeeCents::from_bp_cents(0),
             deposit_sol_fee: FeeCents::from_bp_cents(0),
             deposit_stake_account_fee: FeeCents::from_bp_cents(0),
+            unlisted_stake_deposit_enabled: false,
+            unlisted_stake_deposit_fee: FeeCents::from_bp_cents(0),
             withdraw_stake_account_enabled: false,
             last_stake_move_epoch: 0,
             stake_moved: 0,
             max_stake_moved_per_epoch: Fee::from_basis_points(10000), // 100% of total_lamports_under_control
             delinquent_upgrader: DelinquentUpgraderState::Done,
+            permissionless_add_validator_enabled: false,
+            min_validator_bond: 0,
+            expected_epoch_reward_rate: FeeCents::from_bp_cents(0),
+            max_validator_stake_share: Fee::from_basis_points(0), // no global cap
+            max_validator_commission: 0,
+            max_delinquent_slots: 0,
+            pending_scores_count: 0,
+            pending_scores_epoch: 0,
+            stake_target_hysteresis: Fee::from_basis_points(0),
+            unstake_lowest_score_first: false,
+            max_stake_account_size: 0,
+            seconds_for_stake_delta: 0,
+            early_stake_delta_threshold: 0,
+            total_capped_stake: 0,
+            native_redelegate_enabled: false,
+            new_warmup_cooldown_rate_epoch: 0,
         });
 
         emit!(InitializeEvent {
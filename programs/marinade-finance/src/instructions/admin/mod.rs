@@ -6,6 +6,8 @@ pub mod emergency_pause;
 pub mod initialize;
 pub mod realloc_stake_list;
 pub mod realloc_validator_list;
+pub mod resize_validator_records;
+pub mod slash_validator_bond;
 
 pub use change_authority::*;
 pub use config_lp::*;
@@ -15,3 +17,5 @@ pub use emergency_pause::*;
 pub use initialize::*;
 pub use realloc_stake_list::*;
 pub use realloc_validator_list::*;
+pub use resize_validator_records::*;
+pub use slash_validator_bond::*;
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarinadeError,
+    events::admin::ResizeValidatorRecordsEvent,
+    state::validator_system::{ValidatorList, ValidatorRecord},
+    State,
+};
+
+#[derive(Accounts)]
+pub struct ResizeValidatorRecords<'info> {
+    #[account(
+        mut,
+        has_one = admin_authority @ MarinadeError::InvalidAdminAuthority,
+    )]
+    pub state: Account<'info, State>,
+    pub admin_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+}
+
+impl<'info> ResizeValidatorRecords<'info> {
+    /// Migration of validator lists created with a record size smaller than ValidatorRecord::SERIALIZED_LEN.
+    /// The account can grow only by 10KB per instruction, so realloc_validator_list must be run first
+    /// (repeatedly) up to a capacity of at least count * record_size / current record size
+    pub fn process(&mut self, record_size: u32) -> Result<()> {
+        require_gte!(
+            record_size,
+            ValidatorRecord::SERIALIZED_LEN,
+            MarinadeError::ValidatorRecordSizeIsTooSmall
+        );
+        let old_record_size = self.state.validator_system.validator_record_size();
+        self.state.validator_system.resize_records(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            record_size,
+        )?;
+        emit!(ResizeValidatorRecordsEvent {
+            state: self.state.key(),
+            count: self.state.validator_system.validator_count(),
+            old_record_size,
+            new_record_size: record_size,
+        });
+        Ok(())
+    }
+}
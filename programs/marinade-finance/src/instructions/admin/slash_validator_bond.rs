@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarinadeError, events::admin::SlashValidatorBondEvent, require_lte,
+    state::validator_bond::ValidatorBond, State,
+};
+
+#[derive(Accounts)]
+pub struct SlashValidatorBond<'info> {
+    #[account(
+        mut,
+        has_one = admin_authority @ MarinadeError::InvalidAdminAuthority
+    )]
+    pub state: Account<'info, State>,
+    pub admin_authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = state,
+    )]
+    pub validator_bond: Account<'info, ValidatorBond>,
+    #[account(
+        mut,
+        seeds = [
+            &state.key().to_bytes(),
+            State::RESERVE_SEED
+        ],
+        bump = state.reserve_bump_seed
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SlashValidatorBond<'info> {
+    pub fn process(&mut self, lamports: u64) -> Result<()> {
+        let bond_balance =
+            ValidatorBond::balance(&self.validator_bond.to_account_info(), &self.rent);
+        require_lte!(
+            lamports,
+            bond_balance,
+            MarinadeError::SlashingMoreThanValidatorBond
+        );
+
+        // the bond account is owned by this program so the lamports can be moved directly
+        **self
+            .validator_bond
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= lamports;
+        **self.reserve_pda.try_borrow_mut_lamports()? += lamports;
+        self.state.on_transfer_to_reserve(lamports);
+
+        emit!(SlashValidatorBondEvent {
+            state: self.state.key(),
+            validator: self.validator_bond.validator_vote,
+            lamports,
+            bond_balance: bond_balance - lamports,
+        });
+
+        Ok(())
+    }
+}
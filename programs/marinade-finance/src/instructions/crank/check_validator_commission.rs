@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    checks::get_vote_account_commission,
+    error::MarinadeError,
+    events::{crank::CheckValidatorCommissionEvent, BoolValueChange},
+    state::validator_system::ValidatorList,
+    State,
+};
+
+#[derive(Accounts)]
+pub struct CheckValidatorCommission<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    /// CHECK: parsed in code
+    pub validator_vote: UncheckedAccount<'info>,
+}
+
+impl<'info> CheckValidatorCommission<'info> {
+    /// Permissionless. Zeroes the effective score (and so the stake target) of a validator
+    /// whose commission is above state.max_validator_commission, restores it when it is back under
+    /// or the limit is disabled.
+    /// Only in the stake-delta window, the scores don't change mid-epoch (see schedule_validator_scores)
+    pub fn process(&mut self, validator_index: u32) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        self.state
+            .check_stake_delta_window(&Clock::get()?, &EpochSchedule::get()?)?;
+
+        let mut validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.borrow(),
+            validator_index,
+            self.validator_vote.key,
+        )?;
+
+        let commission = get_vote_account_commission(&self.validator_vote)?;
+        let commission_exceeded_change = {
+            let old = validator.commission_exceeded;
+            let commission_exceeded = self.state.is_commission_exceeded(commission);
+            self.state
+                .update_validator_eligibility(&mut validator, |validator| {
+                    validator.commission_exceeded = commission_exceeded
+                });
+            BoolValueChange {
+                old,
+                new: validator.commission_exceeded,
+            }
+        };
+        self.state.validator_system.set(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+
+        emit!(CheckValidatorCommissionEvent {
+            state: self.state.key(),
+            validator: validator.validator_account,
+            index: validator_index,
+            commission,
+            max_commission: self.state.max_validator_commission,
+            commission_exceeded_change,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    checks::get_vote_account_last_vote_slot,
+    error::MarinadeError,
+    events::{crank::CheckValidatorDelinquencyEvent, BoolValueChange},
+    state::validator_system::ValidatorList,
+    State,
+};
+
+#[derive(Accounts)]
+pub struct CheckValidatorDelinquency<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    /// CHECK: parsed in code
+    pub validator_vote: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> CheckValidatorDelinquency<'info> {
+    /// Permissionless. Marks a validator without votes for more than state.max_delinquent_slots as delinquent:
+    /// its effective score is 0 and its stakes can be emergency unstaked without the manager signature.
+    /// The mark is removed when the validator votes again.
+    /// Like check_validator_commission it only runs in the stake-delta window
+    pub fn process(&mut self, validator_index: u32) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        self.state
+            .check_stake_delta_window(&self.clock, &EpochSchedule::get()?)?;
+
+        let mut validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.borrow(),
+            validator_index,
+            self.validator_vote.key,
+        )?;
+
+        // a closed vote account has no last vote slot
+        let last_vote_slot = get_vote_account_last_vote_slot(&self.validator_vote).unwrap_or(None);
+        let delinquent_slots = self.clock.slot.saturating_sub(last_vote_slot.unwrap_or(0));
+        let delinquent_change = {
+            let old = validator.delinquent;
+            let delinquent = self
+                .state
+                .is_vote_account_delinquent(&self.validator_vote, &self.clock);
+            self.state
+                .update_validator_eligibility(&mut validator, |validator| {
+                    validator.delinquent = delinquent
+                });
+            BoolValueChange {
+                old,
+                new: validator.delinquent,
+            }
+        };
+        self.state.validator_system.set(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+
+        emit!(CheckValidatorDelinquencyEvent {
+            state: self.state.key(),
+            validator: validator.validator_account,
+            index: validator_index,
+            last_vote_slot,
+            delinquent_slots,
+            max_delinquent_slots: self.state.max_delinquent_slots,
+            delinquent_change,
+        });
+
+        Ok(())
+    }
+}
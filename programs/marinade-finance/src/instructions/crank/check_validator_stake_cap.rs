@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarinadeError,
+    events::{crank::CheckValidatorStakeCapEvent, U64ValueChange},
+    state::validator_system::ValidatorList,
+    State,
+};
+
+#[derive(Accounts)]
+pub struct CheckValidatorStakeCap<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    /// CHECK: only the key is used
+    pub validator_vote: UncheckedAccount<'info>,
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            State::RESERVE_SEED
+        ],
+        bump = state.reserve_bump_seed
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+}
+
+impl<'info> CheckValidatorStakeCap<'info> {
+    /// Permissionless. Caps a validator whose score share of the stake not taken by capped validators
+    /// is over its stake cap: its target becomes the cap and its score leaves total_validator_score,
+    /// so the stake cut off by the cap goes to the other validators. Uncaps it when the share is back under.
+    /// Capping a validator raises the share of the others, so it's run over the list until nothing changes,
+    /// and again after changes of the caps, scores or eligibility.
+    /// Runs in the stake-delta window only, when the stake delta it caps against is applied
+    pub fn process(&mut self, validator_index: u32) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        self.state
+            .check_stake_delta_window(&Clock::get()?, &EpochSchedule::get()?)?;
+
+        let mut validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.borrow(),
+            validator_index,
+            self.validator_vote.key,
+        )?;
+
+        // the same total as stake_reserve and deactivate_stake use
+        let stake_delta = self.state.stake_delta(self.reserve_pda.lamports());
+        let total_stake_target = u64::try_from(
+            (self.state.validator_system.total_active_balance as i128 + stake_delta).max(0),
+        )
+        .expect("Stake target overflow");
+        let stake_cap = self.state.validator_stake_cap(&validator);
+
+        let capped_stake_change = {
+            let old = validator.capped_stake;
+            self.state
+                .update_validator_capped_stake(&mut validator, total_stake_target)?;
+            U64ValueChange {
+                old,
+                new: validator.capped_stake,
+            }
+        };
+        self.state.validator_system.set(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+
+        emit!(CheckValidatorStakeCapEvent {
+            state: self.state.key(),
+            validator: validator.validator_account,
+            index: validator_index,
+            stake_cap,
+            total_stake_target,
+            capped_stake_change,
+            total_capped_stake: self.state.total_capped_stake,
+        });
+
+        Ok(())
+    }
+}
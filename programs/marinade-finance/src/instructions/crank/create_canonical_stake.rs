@@ -5,7 +5,7 @@ use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::stake::{Stake, StakeAccount};
 
 use crate::events::crank::CreateCanonicalStakeEvent;
-use crate::state::stake_system::StakeList;
+use crate::state::stake_system::{StakeList, StakeStatus};
 use crate::state::validator_system::ValidatorList;
 use crate::{error::MarinadeError, state::stake_system::StakeSystem, State};
 
@@ -70,9 +70,15 @@ impl<'info> CreateCanonicalStake<'info> {
             MarinadeError::DelinquentUpgraderIsNotDone
         );
 
-        let validator = self.state.validator_system.get(
+        // the indexes are hints, the records are looked up by the account keys if they were moved
+        let (validator_index, validator) = self.state.validator_system.get_by_key(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
+            &self
+                .source_stake
+                .delegation()
+                .ok_or(error!(MarinadeError::SourceStakeMustBeDelegated))?
+                .voter_pubkey,
         )?;
 
         // record for event
@@ -97,7 +103,7 @@ impl<'info> CreateCanonicalStake<'info> {
         );
 
         // Source stake
-        let source_stake_info = self.state.stake_system.get_checked(
+        let (source_stake_index, source_stake_info) = self.state.stake_system.get_by_key(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             source_stake_index,
             self.source_stake.to_account_info().key,
@@ -199,7 +205,7 @@ impl<'info> CreateCanonicalStake<'info> {
             source_delegation.stake,
             &self.clock,
             false, // is_emergency_unstaking? no
-            true,  // is_active? yes
+            StakeStatus::Active,
         )?;
 
         // Call this last because of index invalidation
@@ -86,7 +86,8 @@ impl<'info> DeactivateStake<'info> {
     pub fn process(&mut self, stake_index: u32, validator_index: u32) -> Result<()> {
         require!(!self.state.paused, MarinadeError::ProgramIsPaused);
 
-        let mut stake = self.state.stake_system.get_checked(
+        // the indexes are hints, the records are looked up by the account keys if they were moved
+        let (stake_index, mut stake) = self.state.stake_system.get_by_key(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             stake_index,
             self.stake_account.to_account_info().key,
@@ -97,9 +98,9 @@ impl<'info> DeactivateStake<'info> {
             self.state.delinquent_upgrader.is_done(),
             MarinadeError::DelinquentUpgraderIsNotDone
         );
-        require_eq!(
-            stake.last_update_status,
-            StakeStatus::Active,
+        // activating stakes can be deactivated too
+        require!(
+            stake.last_update_status.is_active_or_activating(),
             MarinadeError::RequiredActiveStake
         );
         // check the account is not already in emergency_unstake
@@ -108,19 +109,21 @@ impl<'info> DeactivateStake<'info> {
             MarinadeError::StakeAccountIsEmergencyUnstaking
         );
 
-        let mut validator = self.state.validator_system.get(
+        let (validator_index, mut validator) = self.state.validator_system.get_by_key(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
+            &self
+                .stake_account
+                .delegation()
+                .ok_or(error!(MarinadeError::StakeNotDelegated))?
+                .voter_pubkey,
         )?;
 
-        // check that we're in the last slots of the epoch (stake-delta window)
-        require_gte!(
-            self.clock.slot,
-            self.epoch_schedule
-                .get_last_slot_in_epoch(self.clock.epoch)
-                .saturating_sub(self.state.stake_system.slots_for_stake_delta),
-            MarinadeError::TooEarlyForStakeDelta
-        );
+        self.state.check_no_pending_scores_due(&self.clock)?;
+
+        // check that we're at the end of the epoch (stake-delta window)
+        self.state
+            .check_stake_delta_window(&self.clock, &self.epoch_schedule)?;
 
         // compute total required stake delta (i128, must be negative)
         let total_stake_delta_i128 = self.state.stake_delta(self.reserve_pda.lamports());
@@ -145,22 +148,34 @@ impl<'info> DeactivateStake<'info> {
         )?;
 
         // compute target for this particular validator (total_stake_target * score/total_score)
+        // over-cap validators are over target
         let validator_stake_target = self
             .state
-            .validator_system
             .validator_stake_target(&validator, total_stake_target)?;
 
         // compute how much we should unstake from this validator
         let validator_active_balance = validator.active_balance; // record for event
-        if validator_active_balance <= validator_stake_target {
+        if validator_active_balance
+            <= self
+                .state
+                .validator_unstake_bound(validator_stake_target, total_stake_delta_i128)
+        {
             msg!(
-                "Validator {} has already reached unstake target {}",
+                "Validator {} has already reached unstake target {} (hysteresis {})",
                 validator.validator_account,
-                validator_stake_target
+                validator_stake_target,
+                self.state.stake_target_hysteresis
             );
             self.return_unused_split_stake_account_rent()?;
             return Ok(()); // Not an error. Don't fail other instructions in tx
         }
+        if self.state.unstake_lowest_score_first {
+            self.check_no_lower_score_over_target(
+                validator_index,
+                validator.effective_score(),
+                total_stake_target,
+            )?;
+        }
         let unstake_from_validator = validator_active_balance - validator_stake_target;
         msg!(
             "unstake {} from_validator {}",
@@ -245,7 +260,7 @@ impl<'info> DeactivateStake<'info> {
                     split_amount,
                     &self.clock,
                     false, // is_emergency_unstaking? no
-                    false, // is_active? no
+                    StakeStatus::Deactivating,
                 )?;
 
                 let split_instruction = stake::instruction::split(
@@ -346,6 +361,45 @@ impl<'info> DeactivateStake<'info> {
         Ok(())
     }
 
+    // scan the whole validator list for a validator with a lower score than the one
+    // being unstaked that is still over its target (including the hysteresis band).
+    // The scan is bounded by the capacity of the validator list account
+    fn check_no_lower_score_over_target(
+        &self,
+        validator_index: u32,
+        effective_score: u32,
+        total_stake_target: u64,
+    ) -> Result<()> {
+        let validator_list = self.validator_list.to_account_info();
+        let validator_list_data = validator_list.data.borrow();
+        for index in 0..self.state.validator_system.validator_count() {
+            if index == validator_index {
+                continue;
+            }
+            let other = self
+                .state
+                .validator_system
+                .get(&validator_list_data, index)?;
+            if other.effective_score() >= effective_score {
+                continue;
+            }
+            let other_stake_target = self
+                .state
+                .validator_stake_target(&other, total_stake_target)?;
+            if other.active_balance > self.state.validator_stake_upper_bound(other_stake_target) {
+                msg!(
+                    "Validator {} with lower score {} is over target {}, stake {}",
+                    other.validator_account,
+                    other.effective_score(),
+                    other_stake_target,
+                    other.active_balance
+                );
+                return err!(MarinadeError::LowerScoreValidatorOverTarget);
+            }
+        }
+        Ok(())
+    }
+
     pub fn return_unused_split_stake_account_rent(&self) -> Result<()> {
         // Return back the rent reserve of unused split stake account in case of early return
         withdraw(
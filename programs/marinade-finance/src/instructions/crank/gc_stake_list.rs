@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::stake::state::StakeState;
+use anchor_lang::solana_program::{stake, sysvar::stake_history};
+use anchor_spl::stake::{withdraw, Stake, Withdraw};
+
+use crate::checks::get_stake_effective_deactivating;
+use crate::events::crank::GcStakeListEvent;
+use crate::state::stake_system::{StakeList, StakeSystem};
+use crate::{error::MarinadeError, require_lte, State};
+
+#[derive(Accounts)]
+pub struct GcStakeList<'info> {
+    #[account(mut)]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_WITHDRAW_SEED
+        ],
+        bump = state.stake_system.stake_withdraw_bump_seed
+    )]
+    pub stake_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: any account, receives the rent of the collected stake accounts
+    #[account(mut)]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: entries are read in place by get_stake_history_entry
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+
+    pub stake_program: Program<'info, Stake>,
+}
+
+impl<'info> GcStakeList<'info> {
+    /// Scans the stake records from start_index, one stake account per record is passed
+    /// as remaining accounts in the list order. Records without delegated lamports
+    /// whose stake account is closed or holds no stake are removed and the rent left
+    /// in the stake account is the reward of the caller.
+    /// The freed space can be reclaimed by the admin with realloc_stake_list
+    //
+    // fn gc_stake_list()
+    pub fn process(
+        &mut self,
+        start_index: u32,
+        stake_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        require!(
+            self.state.delinquent_upgrader.is_done(),
+            MarinadeError::DelinquentUpgraderIsNotDone
+        );
+        require_lte!(
+            start_index as usize + stake_accounts.len(),
+            self.state.stake_system.stake_count() as usize,
+            MarinadeError::BatchAccountsMismatch
+        );
+
+        let mut removed_stake_accounts = vec![];
+        let mut reclaimed_rent: u64 = 0;
+        // backwards, so removing a record never moves the records of the page still to scan.
+        // The last record of the list takes its place: an already scanned one when the page
+        // reaches the end of the list, otherwise one left for another gc_stake_list page
+        for (offset, stake_account) in stake_accounts.iter().enumerate().rev() {
+            let index = start_index + offset as u32;
+            let stake = self.state.stake_system.get_checked(
+                &self.stake_list.to_account_info().data.as_ref().borrow(),
+                index,
+                stake_account.key,
+            )?;
+            if stake.last_update_delegated_lamports != 0 {
+                continue;
+            }
+            if let Some(rent) = self.collectable_rent(stake_account)? {
+                if rent > 0 {
+                    self.withdraw_rent(stake_account, rent)?;
+                    reclaimed_rent += rent;
+                }
+                msg!("Remove stake {} from the list", stake.stake_account);
+                self.state.stake_system.remove(
+                    &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+                    index,
+                )?;
+                removed_stake_accounts.push(stake.stake_account);
+            }
+        }
+
+        emit!(GcStakeListEvent {
+            state: self.state.key(),
+            start_index,
+            scanned_count: stake_accounts.len() as u32,
+            removed_stake_accounts,
+            reclaimed_rent,
+            stake_count: self.state.stake_system.stake_count(),
+        });
+        Ok(())
+    }
+
+    // None when the stake account still holds stake, otherwise the lamports left in it
+    fn collectable_rent(&self, stake_account: &AccountInfo<'info>) -> Result<Option<u64>> {
+        if *stake_account.owner != stake::program::ID {
+            // closed
+            return Ok(Some(0));
+        }
+        let stake_state = StakeState::deserialize(&mut &stake_account.data.borrow()[..])?;
+        let meta = if let Some(meta) = stake_state.meta() {
+            meta
+        } else {
+            // uninitialized
+            return Ok(None);
+        };
+        if meta.authorized.withdrawer != self.stake_withdraw_authority.key() {
+            return Ok(None);
+        }
+        if stake_account.lamports() > meta.rent_exempt_reserve {
+            return Ok(None);
+        }
+        if let Some(delegation) = stake_state.delegation() {
+            if delegation.deactivation_epoch == std::u64::MAX
+                || get_stake_effective_deactivating(
+                    &delegation,
+                    self.clock.epoch,
+                    &self.stake_history,
+                    self.state.new_warmup_cooldown_rate_epoch,
+                )? > 0
+            {
+                // the stake program does not allow to withdraw
+                return Ok(None);
+            }
+        }
+        Ok(Some(stake_account.lamports()))
+    }
+
+    fn withdraw_rent(&self, stake_account: &AccountInfo<'info>, rent: u64) -> Result<()> {
+        withdraw(
+            CpiContext::new_with_signer(
+                self.stake_program.to_account_info(),
+                Withdraw {
+                    stake: stake_account.clone(),
+                    withdrawer: self.stake_withdraw_authority.to_account_info(),
+                    to: self.rent_collector.to_account_info(),
+                    clock: self.clock.to_account_info(),
+                    stake_history: self.stake_history.to_account_info(),
+                },
+                &[&[
+                    &self.state.key().to_bytes(),
+                    StakeSystem::STAKE_WITHDRAW_SEED,
+                    &[self.state.stake_system.stake_withdraw_bump_seed],
+                ]],
+            ),
+            rent,
+            None,
+        )
+    }
+}
@@ -0,0 +1,286 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::stake_history;
+use anchor_lang::solana_program::{program::invoke_signed, stake};
+use anchor_spl::stake::{withdraw, Stake, StakeAccount, Withdraw};
+
+use crate::events::crank::MergeStakeAccountsEvent;
+use crate::state::stake_system::{StakeList, StakeRecord, StakeStatus};
+use crate::state::validator_system::ValidatorList;
+use crate::{error::MarinadeError, state::stake_system::StakeSystem, State};
+
+#[derive(Accounts)]
+pub struct MergeStakeAccounts<'info> {
+    #[account(
+        mut,
+        has_one = operational_sol_account
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    #[account(mut)]
+    pub destination_stake: Box<Account<'info, StakeAccount>>,
+    #[account(mut)]
+    pub source_stake: Box<Account<'info, StakeAccount>>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_DEPOSIT_SEED
+        ],
+        bump = state.stake_system.stake_deposit_bump_seed
+    )]
+    pub stake_deposit_authority: UncheckedAccount<'info>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_WITHDRAW_SEED
+        ],
+        bump = state.stake_system.stake_withdraw_bump_seed
+    )]
+    pub stake_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: not important
+    #[account(mut)]
+    pub operational_sol_account: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: have no CPU budget to parse
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+
+    pub stake_program: Program<'info, Stake>,
+}
+
+impl<'info> MergeStakeAccounts<'info> {
+    /// Merges any two stake accounts of the stake list delegated to the same validator
+    /// without the canonical stake flow of merge_stakes. Both stakes must be active,
+    /// or both deactivating (in the same epoch with the same emergency flag),
+    /// the stake program only allows the later to be merged once they are cooled down.
+    /// The source record is removed and its rent is returned to operational_sol_account
+    pub fn process(
+        &mut self,
+        destination_stake_index: u32,
+        source_stake_index: u32,
+        validator_index: u32,
+    ) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        require!(
+            self.state.delinquent_upgrader.is_done(),
+            MarinadeError::DelinquentUpgraderIsNotDone
+        );
+        require_keys_neq!(
+            self.destination_stake.key(),
+            self.source_stake.key(),
+            MarinadeError::IncompatibleStakesToMerge
+        );
+
+        // record for event
+        let operational_sol_balance = self.operational_sol_account.lamports();
+
+        // the indexes are hints, the records are looked up by the account keys if they were moved
+        let (destination_stake_index, mut destination_stake_info) =
+            self.state.stake_system.get_by_key(
+                &self.stake_list.to_account_info().data.as_ref().borrow(),
+                destination_stake_index,
+                self.destination_stake.to_account_info().key,
+            )?;
+        let (source_stake_index, source_stake_info) = self.state.stake_system.get_by_key(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            source_stake_index,
+            self.source_stake.to_account_info().key,
+        )?;
+        let destination_delegation = self.destination_stake.delegation().ok_or_else(|| {
+            error!(MarinadeError::DestinationStakeMustBeDelegated)
+                .with_account_name("destination_stake")
+        })?;
+        let source_delegation = self.source_stake.delegation().ok_or_else(|| {
+            error!(MarinadeError::SourceStakeMustBeDelegated).with_account_name("source_stake")
+        })?;
+        require_keys_eq!(
+            source_delegation.voter_pubkey,
+            destination_delegation.voter_pubkey,
+            MarinadeError::InvalidSourceStakeDelegation
+        );
+        require_eq!(
+            source_stake_info.last_update_status,
+            destination_stake_info.last_update_status,
+            MarinadeError::IncompatibleStakesToMerge
+        );
+        require_eq!(
+            source_delegation.deactivation_epoch,
+            destination_delegation.deactivation_epoch,
+            MarinadeError::IncompatibleStakesToMerge
+        );
+        let is_active = match destination_stake_info.last_update_status {
+            StakeStatus::Active => {
+                require_eq!(
+                    destination_delegation.deactivation_epoch,
+                    std::u64::MAX,
+                    MarinadeError::DestinationStakeMustNotBeDeactivating
+                );
+                true
+            }
+            StakeStatus::Deactivating => {
+                require_neq!(
+                    destination_delegation.deactivation_epoch,
+                    std::u64::MAX,
+                    MarinadeError::RequiredDeactivatingStake
+                );
+                // the cooling down lamports are counted in emergency_cooling_down or delayed_unstake_cooling_down
+                require_eq!(
+                    source_stake_info.is_emergency_unstaking,
+                    destination_stake_info.is_emergency_unstaking,
+                    MarinadeError::IncompatibleStakesToMerge
+                );
+                false
+            }
+            _ => return err!(MarinadeError::IncompatibleStakesToMerge),
+        };
+
+        let last_update_destination_stake_delegation =
+            destination_stake_info.last_update_delegated_lamports;
+        let source_rent = self.source_stake.meta().unwrap().rent_exempt_reserve;
+
+        self.merge()?;
+        self.destination_stake.reload()?;
+
+        let (extra_delegated, returned_stake_rent) = if is_active {
+            self.on_active_merged(
+                &mut destination_stake_info,
+                &source_stake_info,
+                validator_index,
+                source_rent,
+            )?
+        } else {
+            // lamports of the cooled down stakes are now in the destination,
+            // the rewards of both are collected by update_deactivated
+            destination_stake_info.last_update_delegated_lamports +=
+                source_stake_info.last_update_delegated_lamports;
+            (0, source_rent)
+        };
+
+        self.state.stake_system.set(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            destination_stake_index,
+            destination_stake_info,
+        )?;
+        // Call this last because of index invalidation
+        self.state.stake_system.remove(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            source_stake_index,
+        )?;
+        if returned_stake_rent > 0 {
+            // withdraw the rent-exempt lamports part of merged stake to operational_sol_account for the future recreation of this slot's account
+            withdraw(
+                CpiContext::new_with_signer(
+                    self.stake_program.to_account_info(),
+                    Withdraw {
+                        stake: self.destination_stake.to_account_info(),
+                        withdrawer: self.stake_withdraw_authority.to_account_info(),
+                        to: self.operational_sol_account.to_account_info(),
+                        clock: self.clock.to_account_info(),
+                        stake_history: self.stake_history.to_account_info(),
+                    },
+                    &[&[
+                        &self.state.key().to_bytes(),
+                        StakeSystem::STAKE_WITHDRAW_SEED,
+                        &[self.state.stake_system.stake_withdraw_bump_seed],
+                    ]],
+                ),
+                returned_stake_rent,
+                None,
+            )?;
+        }
+        emit!(MergeStakeAccountsEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            status: destination_stake_info.last_update_status,
+            destination_stake_index,
+            destination_stake_account: destination_stake_info.stake_account,
+            last_update_destination_stake_delegation,
+            source_stake_index,
+            source_stake_account: source_stake_info.stake_account,
+            last_update_source_stake_delegation: source_stake_info.last_update_delegated_lamports,
+            validator_vote: destination_delegation.voter_pubkey,
+            extra_delegated,
+            returned_stake_rent,
+            total_active_balance: self.state.validator_system.total_active_balance,
+            operational_sol_balance,
+        });
+        Ok(())
+    }
+
+    fn merge(&self) -> Result<()> {
+        invoke_signed(
+            &stake::instruction::merge(
+                self.destination_stake.to_account_info().key,
+                self.source_stake.to_account_info().key,
+                self.stake_deposit_authority.to_account_info().key,
+            )[0],
+            &[
+                self.stake_program.to_account_info(),
+                self.destination_stake.to_account_info(),
+                self.source_stake.to_account_info(),
+                self.clock.to_account_info(),
+                self.stake_history.to_account_info(),
+                self.stake_deposit_authority.to_account_info(),
+            ],
+            &[&[
+                &self.state.key().to_bytes(),
+                StakeSystem::STAKE_DEPOSIT_SEED,
+                &[self.state.stake_system.stake_deposit_bump_seed],
+            ]],
+        )?;
+        Ok(())
+    }
+
+    // the same accounting as merge_stakes does for the canonical stake,
+    // returns (extra_delegated, returned_stake_rent)
+    fn on_active_merged(
+        &mut self,
+        destination_stake_info: &mut StakeRecord,
+        source_stake_info: &StakeRecord,
+        validator_index: u32,
+        source_rent: u64,
+    ) -> Result<(u64, u64)> {
+        let destination_stake = self.destination_stake.delegation().unwrap().stake;
+        // both stakes must be updated, the merged delegation is checked against the records
+        let extra_delegated = destination_stake
+            .checked_sub(
+                destination_stake_info.last_update_delegated_lamports
+                    + source_stake_info.last_update_delegated_lamports,
+            )
+            .ok_or_else(|| error!(MarinadeError::SourceStakeMustBeUpdated))?;
+        // rent-lamports of a source not fully active are delegated on top of the destination delegation
+        let returned_stake_rent = source_rent.saturating_sub(extra_delegated);
+
+        let (validator_index, mut validator) = self.state.validator_system.get_by_key(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &self.destination_stake.delegation().unwrap().voter_pubkey,
+        )?;
+        validator.active_balance += extra_delegated;
+        self.state.validator_system.set(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+        self.state.validator_system.total_active_balance += extra_delegated;
+
+        destination_stake_info.last_update_delegated_lamports = destination_stake;
+        Ok((extra_delegated, returned_stake_rent))
+    }
+}
@@ -73,9 +73,15 @@ impl<'info> MergeStakes<'info> {
             MarinadeError::DelinquentUpgraderIsNotDone
         );
 
-        let mut validator = self.state.validator_system.get(
+        // the indexes are hints, the records are looked up by the account keys if they were moved
+        let (validator_index, mut validator) = self.state.validator_system.get_by_key(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
+            &self
+                .destination_stake
+                .delegation()
+                .ok_or(error!(MarinadeError::DestinationStakeMustBeDelegated))?
+                .voter_pubkey,
         )?;
 
         // record for event
@@ -91,11 +97,12 @@ impl<'info> MergeStakes<'info> {
             canonical_stake_account,
             MarinadeError::InvalidCanonicalStakeAccountAddress
         );
-        let mut canonical_stake_info = self.state.stake_system.get_checked(
-            &self.stake_list.to_account_info().data.as_ref().borrow(),
-            canonical_stake_index,
-            self.destination_stake.to_account_info().key,
-        )?;
+        let (canonical_stake_index, mut canonical_stake_info) =
+            self.state.stake_system.get_by_key(
+                &self.stake_list.to_account_info().data.as_ref().borrow(),
+                canonical_stake_index,
+                self.destination_stake.to_account_info().key,
+            )?;
         let last_update_canonical_stake_delegation =
             canonical_stake_info.last_update_delegated_lamports;
         let canonical_delegation = if let Some(delegation) = self.destination_stake.delegation() {
@@ -126,7 +133,7 @@ impl<'info> MergeStakes<'info> {
         );
 
         // Source stake
-        let source_stake_info = self.state.stake_system.get_checked(
+        let (source_stake_index, source_stake_info) = self.state.stake_system.get_by_key(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             source_stake_index,
             self.source_stake.to_account_info().key,
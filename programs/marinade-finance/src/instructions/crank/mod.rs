@@ -1,13 +1,29 @@
+pub mod check_validator_commission;
+pub mod check_validator_delinquency;
+pub mod check_validator_stake_cap;
 pub mod create_canonical_stake;
 pub mod deactivate_stake;
 pub mod finalize_delinquent_upgrade;
+pub mod gc_stake_list;
+pub mod merge_stake_accounts;
 pub mod merge_stakes;
+pub mod promote_validator_scores;
+pub mod reconcile_deactivated_stake;
+pub mod settle_validator_bond;
 pub mod stake_reserve;
 pub mod update;
 
+pub use check_validator_commission::*;
+pub use check_validator_delinquency::*;
+pub use check_validator_stake_cap::*;
 pub use create_canonical_stake::*;
 pub use deactivate_stake::*;
 pub use finalize_delinquent_upgrade::*;
+pub use gc_stake_list::*;
+pub use merge_stake_accounts::*;
 pub use merge_stakes::*;
+pub use promote_validator_scores::*;
+pub use reconcile_deactivated_stake::*;
+pub use settle_validator_bond::*;
 pub use stake_reserve::*;
 pub use update::*;
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarinadeError,
+    events::{crank::PromoteValidatorScoresEvent, U32ValueChange},
+    state::validator_system::ValidatorList,
+    State,
+};
+
+#[derive(Accounts)]
+pub struct PromoteValidatorScores<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> PromoteValidatorScores<'info> {
+    /// Permissionless. Promotes the due pending scores of a page of the validator list.
+    /// stake_reserve and deactivate_stake are blocked until all the due scores are promoted
+    pub fn process(&mut self, start_index: u32, max_validators: u32) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+
+        let end_index = start_index
+            .saturating_add(max_validators)
+            .min(self.state.validator_system.validator_count());
+        let validator_list_info = self.validator_list.to_account_info();
+        let mut validator_list_data = validator_list_info.data.borrow_mut();
+        let mut total_validator_score = self.state.validator_system.total_validator_score;
+        let mut promoted_count: u32 = 0;
+        for index in start_index..end_index {
+            let mut validator = self
+                .state
+                .validator_system
+                .get(&validator_list_data, index)?;
+            if validator.pending_score_epoch == 0
+                || validator.pending_score_epoch > self.clock.epoch
+            {
+                continue;
+            }
+            total_validator_score -= validator.effective_score();
+            validator.score = validator.pending_score;
+            total_validator_score += validator.effective_score();
+            validator.pending_score = 0;
+            validator.pending_score_epoch = 0;
+            self.state
+                .validator_system
+                .set(&mut validator_list_data, index, validator)?;
+            promoted_count += 1;
+        }
+        self.state.pending_scores_count -= promoted_count;
+
+        let total_validator_score_change = {
+            let old = self.state.validator_system.total_validator_score;
+            self.state.validator_system.total_validator_score = total_validator_score;
+            U32ValueChange {
+                old,
+                new: total_validator_score,
+            }
+        };
+
+        emit!(PromoteValidatorScoresEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            promoted_count,
+            pending_scores_count: self.state.pending_scores_count,
+            total_validator_score_change,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    error::MarinadeError,
+    events::crank::ReconcileDeactivatedStakeEvent,
+    state::{
+        stake_system::{StakeList, StakeStatus},
+        validator_system::ValidatorList,
+    },
+    State,
+};
+
+use anchor_lang::prelude::*;
+use anchor_spl::stake::StakeAccount;
+
+#[derive(Accounts)]
+pub struct ReconcileDeactivatedStake<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+    pub stake_account: Account<'info, StakeAccount>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> ReconcileDeactivatedStake<'info> {
+    /// The stake program can deactivate stakes of delinquent validators without our signature.
+    /// Moves such a stake, still recorded as active, from total_active_balance to emergency_cooling_down
+    /// the same way emergency_unstake does, so update_active stops failing on it and
+    /// update_deactivated collects it once it is cooled down
+    pub fn process(&mut self, stake_index: u32, validator_index: u32) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        require!(
+            self.state.delinquent_upgrader.is_done(),
+            MarinadeError::DelinquentUpgraderIsNotDone
+        );
+
+        // the indexes are hints, the records are looked up by the account keys if they were moved
+        let (stake_index, mut stake) = self.state.stake_system.get_by_key(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
+        require!(
+            stake.last_update_status.is_active_or_activating(),
+            MarinadeError::RequiredActiveStake
+        );
+        require!(
+            !stake.is_emergency_unstaking,
+            MarinadeError::StakeAccountIsEmergencyUnstaking
+        );
+
+        let delegation = self
+            .stake_account
+            .delegation()
+            .ok_or(error!(MarinadeError::StakeNotDelegated))?;
+        require_neq!(
+            delegation.deactivation_epoch,
+            std::u64::MAX,
+            MarinadeError::RequiredDeactivatingStake
+        );
+
+        let (validator_index, mut validator) = self.state.validator_system.get_by_key(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &delegation.voter_pubkey,
+        )?;
+
+        let amount = stake.last_update_delegated_lamports;
+        msg!(
+            "Stake {} was deactivated in epoch {}, moving {} to emergency cooling down",
+            stake.stake_account,
+            delegation.deactivation_epoch,
+            amount
+        );
+        // record for event
+        let validator_active_balance = validator.active_balance;
+        let total_active_balance = self.state.validator_system.total_active_balance;
+
+        stake.is_emergency_unstaking = true;
+        stake.last_update_status = StakeStatus::Deactivating;
+        validator.active_balance -= amount;
+        self.state.validator_system.total_active_balance -= amount;
+        self.state.emergency_cooling_down += amount;
+
+        // update stake-list & validator-list
+        self.state.stake_system.set(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            stake_index,
+            stake,
+        )?;
+        self.state.validator_system.set(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+
+        emit!(ReconcileDeactivatedStakeEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            stake_index,
+            stake_account: stake.stake_account,
+            validator_index,
+            validator_vote: validator.validator_account,
+            deactivation_epoch: delegation.deactivation_epoch,
+            amount,
+            validator_active_balance,
+            total_active_balance,
+            emergency_cooling_down: self.state.emergency_cooling_down,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+
+use crate::events::crank::BondSettlementEvent;
+use crate::state::validator_bond::ValidatorBond;
+use crate::state::validator_system::ValidatorList;
+use crate::{error::MarinadeError, State};
+
+#[derive(Accounts)]
+pub struct SettleValidatorBond<'info> {
+    #[account(mut)]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    #[account(
+        mut,
+        has_one = state,
+    )]
+    pub validator_bond: Account<'info, ValidatorBond>,
+    #[account(
+        mut,
+        seeds = [
+            &state.key().to_bytes(),
+            State::RESERVE_SEED
+        ],
+        bump = state.reserve_bump_seed
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SettleValidatorBond<'info> {
+    /// Cover the rewards shortfall of the last completed rewards epoch of the validator
+    /// from its bond. The lamports go to the reserve, so they are part of the mSOL price
+    pub fn process(&mut self, validator_index: u32) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+
+        let mut validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &self.validator_bond.validator_vote,
+        )?;
+        validator.roll_rewards_epoch(self.clock.epoch);
+        self.state.validator_system.set(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+
+        if self.validator_bond.last_settled_epoch >= validator.last_rewards_epoch {
+            msg!(
+                "Bond of validator {} is already settled for epoch {}",
+                validator.validator_account,
+                validator.last_rewards_epoch
+            );
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+
+        let shortfall = validator.last_epoch_shortfall();
+        let bond_balance =
+            ValidatorBond::balance(&self.validator_bond.to_account_info(), &self.rent);
+        let settled_lamports = shortfall.min(bond_balance);
+        if settled_lamports > 0 {
+            // the bond account is owned by this program so the lamports can be moved directly
+            **self
+                .validator_bond
+                .to_account_info()
+                .try_borrow_mut_lamports()? -= settled_lamports;
+            **self.reserve_pda.try_borrow_mut_lamports()? += settled_lamports;
+            self.state.on_transfer_to_reserve(settled_lamports);
+        }
+        self.validator_bond.last_settled_epoch = validator.last_rewards_epoch;
+
+        emit!(BondSettlementEvent {
+            state: self.state.key(),
+            epoch: validator.last_rewards_epoch,
+            validator_index,
+            validator_vote: validator.validator_account,
+            rewards: validator.last_epoch_rewards,
+            extra_lamports: validator.last_epoch_extra_lamports,
+            expected_rewards: validator.last_epoch_expected_rewards,
+            shortfall,
+            settled_lamports,
+            bond_balance: bond_balance - settled_lamports,
+        });
+
+        Ok(())
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
+    checks::get_vote_account_commission,
     error::MarinadeError,
     events::crank::StakeReserveEvent,
     state::{
-        stake_system::{StakeList, StakeSystem},
+        stake_system::{StakeList, StakeStatus, StakeSystem},
         validator_system::ValidatorList,
     },
     State, ID,
@@ -140,10 +141,11 @@ impl<'info> StakeReserve<'info> {
         let total_stake_delta = u64::try_from(stake_delta).expect("Stake delta overflow");
         let total_stake_target = total_active_balance.saturating_add(total_stake_delta);
 
-        let mut validator = self
+        // the index is a hint, the record is looked up by the vote account if it was moved
+        let (validator_index, mut validator) = self
             .state
             .validator_system
-            .get_checked(
+            .get_by_key(
                 &self.validator_list.to_account_info().data.as_ref().borrow(),
                 validator_index,
                 self.validator_vote.key,
@@ -152,6 +154,28 @@ impl<'info> StakeReserve<'info> {
         // record for event
         let validator_active_balance = validator.active_balance;
 
+        if validator.migrated_to != Pubkey::default() {
+            msg!(
+                "Validator {} migrated to {}. Please stake into the new vote account",
+                validator.validator_account,
+                validator.migrated_to
+            );
+            self.return_unused_stake_account_rent()?;
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+
+        let commission = get_vote_account_commission(&self.validator_vote)?;
+        if self.state.is_commission_exceeded(commission) {
+            msg!(
+                "Validator {} commission {}% is above max {}%",
+                validator.validator_account,
+                commission,
+                self.state.max_validator_commission
+            );
+            self.return_unused_stake_account_rent()?;
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+
         if validator.last_stake_delta_epoch == self.clock.epoch {
             // check if we have some extra stake runs allowed
             if self.state.stake_system.extra_stake_delta_runs == 0 {
@@ -168,26 +192,41 @@ impl<'info> StakeReserve<'info> {
             }
         }
 
-        let last_slot = self.epoch_schedule.get_last_slot_in_epoch(self.clock.epoch);
+        self.state.check_no_pending_scores_due(&self.clock)?;
 
-        require_gte!(
-            self.clock.slot,
-            last_slot.saturating_sub(self.state.stake_system.slots_for_stake_delta),
-            MarinadeError::TooEarlyForStakeDelta
-        );
+        // big deposits are staked without waiting for the end of the epoch
+        let is_early_stake_delta = !self
+            .state
+            .is_in_stake_delta_window(&self.clock, &self.epoch_schedule)?;
+        if is_early_stake_delta {
+            require!(
+                self.state.early_stake_delta_threshold > 0
+                    && total_stake_delta >= self.state.early_stake_delta_threshold,
+                MarinadeError::TooEarlyForStakeDelta
+            );
+            msg!(
+                "Early stake delta {} over threshold {}",
+                total_stake_delta,
+                self.state.early_stake_delta_threshold
+            );
+        }
 
         let validator_stake_target = self
             .state
-            .validator_system
             .validator_stake_target(&validator, total_stake_target)?;
 
-        //verify the validator is under-staked
-        if validator_active_balance >= validator_stake_target {
+        //verify the validator is under-staked, below the hysteresis band
+        if validator_active_balance
+            >= self
+                .state
+                .validator_stake_lower_bound(validator_stake_target)
+        {
             msg!(
-                    "Validator {} has already reached stake target {}. Please stake into another validator",
-                    validator.validator_account,
-                    validator_stake_target
-                );
+                "Validator {} has already reached stake target {} (hysteresis {})",
+                validator.validator_account,
+                validator_stake_target,
+                self.state.stake_target_hysteresis
+            );
             self.return_unused_stake_account_rent()?;
             return Ok(()); // Not an error. Don't fail other instructions in tx
         }
@@ -199,7 +238,11 @@ impl<'info> StakeReserve<'info> {
             .min(total_stake_delta);
 
         // if what's left in stake_delta after this operation is < state.min_stake, take all the remainder from stake_delta
-        let stake_target = if total_stake_delta - stake_target < self.state.stake_system.min_stake {
+        // unless it goes over the validator stake cap, then the remainder is left for other validators
+        let stake_target = if total_stake_delta - stake_target < self.state.stake_system.min_stake
+            && validator_active_balance + total_stake_delta
+                <= self.state.validator_stake_cap(&validator)
+        {
             total_stake_delta
         } else {
             stake_target
@@ -282,14 +325,18 @@ impl<'info> StakeReserve<'info> {
             stake_target,
             &self.clock,
             false, // is_emergency_unstaking? no
-            true,  // is_active? yes
+            StakeStatus::Activating,
         )?;
 
         // update validator record and store in list
         validator.active_balance += stake_target;
-        validator.last_stake_delta_epoch = self.clock.epoch;
-        // Any stake-delta activity must activate stake delta mode
-        self.state.stake_system.last_stake_delta_epoch = self.clock.epoch;
+        // Any stake-delta activity in the window must activate stake delta mode.
+        // An early stake delta does not: the validator can still be staked or unstaked
+        // in the window and the tickets ordered after it are still unstaked in the window of this epoch
+        if !is_early_stake_delta {
+            validator.last_stake_delta_epoch = self.clock.epoch;
+            self.state.stake_system.last_stake_delta_epoch = self.clock.epoch;
+        }
         self.state.validator_system.set(
             &mut self
                 .validator_list
@@ -321,6 +368,82 @@ impl<'info> StakeReserve<'info> {
         Ok(())
     }
 
+    /// permissionless variant of stake_reserve where the program picks the validator:
+    /// scans the page of validator_list from page_start and only stakes if validator_vote
+    /// is the most under-target validator of the page (largest validator_stake_target - active_balance)
+    /// among the validators process would stake. The commission is checked by the commission_exceeded
+    /// flag, a winner over the max commission not flagged yet is skipped after check_validator_commission
+    //
+    // fn stake_reserve_most_under_target()
+    pub fn process_most_under_target(
+        &mut self,
+        validator_index: u32,
+        page_start: u32,
+        page_len: u32,
+    ) -> Result<()> {
+        let stake_delta = self.state.stake_delta(self.reserve_pda.lamports());
+        if stake_delta > 0 {
+            self.state.check_no_pending_scores_due(&self.clock)?;
+            let total_stake_target = self
+                .state
+                .validator_system
+                .total_active_balance
+                .saturating_add(u64::try_from(stake_delta).expect("Stake delta overflow"));
+            let page_end = page_start
+                .saturating_add(page_len)
+                .min(self.state.validator_system.validator_count());
+            let validator_list = self.validator_list.to_account_info();
+            let validator_list_data = validator_list.data.borrow();
+            let mut most_under_target: Option<(Pubkey, u64)> = None;
+            for index in page_start..page_end {
+                let validator = self
+                    .state
+                    .validator_system
+                    .get(&validator_list_data, index)?;
+                // the same filters as process, so an already staked winner doesn't block the others
+                if !validator.is_eligible()
+                    || (validator.last_stake_delta_epoch == self.clock.epoch
+                        && self.state.stake_system.extra_stake_delta_runs == 0)
+                {
+                    continue;
+                }
+                let validator_stake_target = self
+                    .state
+                    .validator_stake_target(&validator, total_stake_target)?;
+                if validator.active_balance
+                    >= self
+                        .state
+                        .validator_stake_lower_bound(validator_stake_target)
+                {
+                    continue;
+                }
+                let under_target = validator_stake_target - validator.active_balance;
+                if under_target > most_under_target.map_or(0, |(_, amount)| amount) {
+                    most_under_target = Some((validator.validator_account, under_target));
+                }
+            }
+            if let Some((validator_vote, under_target)) = most_under_target {
+                msg!(
+                    "Most under-target validator {} by {}",
+                    validator_vote,
+                    under_target
+                );
+                require_keys_eq!(
+                    *self.validator_vote.key,
+                    validator_vote,
+                    MarinadeError::NotMostUnderTargetValidator
+                );
+            } else {
+                msg!("No under-target validator in the page");
+                drop(validator_list_data);
+                self.return_unused_stake_account_rent()?;
+                return Ok(()); // Not an error. Don't fail other instructions in tx
+            }
+        }
+        // stake_reserve does nothing when there is nothing to stake
+        self.process(validator_index)
+    }
+
     pub fn return_unused_stake_account_rent(&self) -> Result<()> {
         // Return back the rent reserve of unused stake account in case of early return
         withdraw(
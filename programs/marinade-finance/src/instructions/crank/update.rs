@@ -3,12 +3,14 @@
 use std::ops::{Deref, DerefMut};
 
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::sysvar::stake_history;
+use anchor_lang::solana_program::{stake::state::Delegation, sysvar::stake_history};
 use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::stake::{withdraw, Stake, StakeAccount, Withdraw};
 use anchor_spl::token::{mint_to, Mint, MintTo, Token};
 
-use crate::events::crank::{UpdateActiveEvent, UpdateDeactivatedEvent};
+use crate::calc::shares_from_value;
+use crate::checks::{get_stake_effective_and_activating, get_stake_effective_deactivating};
+use crate::events::crank::{UpdateActiveBatchEvent, UpdateActiveEvent, UpdateDeactivatedEvent};
 use crate::events::U64ValueChange;
 use crate::require_lte;
 use crate::state::delinquent_upgrader::DelinquentUpgraderState;
@@ -70,7 +72,7 @@ pub struct UpdateCommon<'info> {
     pub treasury_msol_account: UncheckedAccount<'info>, //receives 1% from staking rewards protocol fee
 
     pub clock: Sysvar<'info, Clock>,
-    /// CHECK: have no CPU budget to parse
+    /// CHECK: entries are read in place by get_stake_history_entry
     #[account(address = stake_history::ID)]
     pub stake_history: UncheckedAccount<'info>,
 
@@ -131,14 +133,21 @@ impl<'info> DerefMut for UpdateDeactivated<'info> {
     }
 }
 
+#[derive(Clone, Copy, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateActiveBatchItem {
+    pub stake_index: u32,
+    pub validator_index: u32,
+}
+
 struct BeginOutput {
+    stake_index: u32,
     stake: StakeRecord,
-    validator: ValidatorRecord,
     is_treasury_msol_ready_for_transfer: bool,
 }
 
 impl<'info> UpdateCommon<'info> {
-    fn begin(&mut self, stake_index: u32, validator_index: u32) -> Result<BeginOutput> {
+    // indexes received are hints, the records are looked up by the account keys if they were moved
+    fn begin(&mut self, stake_index: u32) -> Result<BeginOutput> {
         let is_treasury_msol_ready_for_transfer = self
             .state
             .get_treasury_msol_balance(&self.treasury_msol_account)
@@ -171,7 +180,7 @@ impl<'info> UpdateCommon<'info> {
         }
         self.state.msol_supply = self.msol_mint.supply;
 
-        let stake = self.state.stake_system.get_checked(
+        let (stake_index, stake) = self.state.stake_system.get_by_key(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             stake_index,
             self.stake_account.to_account_info().key,
@@ -180,7 +189,18 @@ impl<'info> UpdateCommon<'info> {
             msg!("Double update for stake {}", stake.stake_account);
             return Ok(()); // Not error. Maybe parallel update artifact
         }*/
-        let validator = self.state.validator_system.get_checked(
+
+        Ok(BeginOutput {
+            stake_index,
+            stake,
+            is_treasury_msol_ready_for_transfer,
+        })
+    }
+
+    // stakes deposited from unlisted validators have no validator record,
+    // so it is only read when the stake is accounted in the validator active balance
+    fn get_validator(&self, validator_index: u32) -> Result<(u32, ValidatorRecord)> {
+        self.state.validator_system.get_by_key(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
             &self
@@ -188,13 +208,7 @@ impl<'info> UpdateCommon<'info> {
                 .delegation()
                 .ok_or(error!(MarinadeError::StakeNotDelegated))?
                 .voter_pubkey,
-        )?;
-
-        Ok(BeginOutput {
-            stake,
-            validator,
-            is_treasury_msol_ready_for_transfer,
-        })
+        )
     }
 
     pub fn withdraw_to_reserve(&mut self, amount: u64) -> Result<()> {
@@ -305,20 +319,106 @@ impl<'info> UpdateActive<'info> {
     //
     // fn update_active()
     pub fn process(&mut self, stake_index: u32, validator_index: u32) -> Result<()> {
+        self.check_update_active()?;
+        self.update_stake(stake_index, validator_index, None)
+    }
+
+    /// Same as update_active for many stake accounts in one instruction.
+    /// The first item is for stake_account, the others are for the stake accounts
+    /// passed as remaining accounts in the same order.
+    /// The protocol fees of all the stakes are minted once at the end and the mSOL price is updated once
+    //
+    // fn update_active_batch()
+    pub fn process_batch(
+        &mut self,
+        items: Vec<UpdateActiveBatchItem>,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        self.check_update_active()?;
+        require_eq!(
+            items.len(),
+            remaining_accounts.len() + 1,
+            MarinadeError::BatchAccountsMismatch
+        );
+        let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
+        let msol_supply = self.state.msol_supply;
+
+        let mut fee_lamports_incoming: u64 = 0;
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                *self.common.stake_account = Account::try_from(&remaining_accounts[i - 1])?;
+            }
+            self.update_stake(
+                item.stake_index,
+                item.validator_index,
+                Some(&mut fee_lamports_incoming),
+            )?;
+        }
+
+        let msol_fees = if self
+            .state
+            .get_treasury_msol_balance(&self.treasury_msol_account)
+            .is_some()
+        {
+            // mSOL for the fee at the price before the batch rewards were added, not at the new higher price
+            let protocol_rewards_fee = self.state.reward_fee.apply(fee_lamports_incoming);
+            msg!("protocol_rewards_fee {}", protocol_rewards_fee);
+            let fee_as_msol_amount = shares_from_value(
+                protocol_rewards_fee,
+                total_virtual_staked_lamports,
+                msol_supply,
+            )?;
+            self.mint_to_treasury(fee_as_msol_amount)?;
+            Some(fee_as_msol_amount)
+        } else {
+            None
+        };
+        let msol_price_change = self.update_msol_price()?;
+
+        emit!(UpdateActiveBatchEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            stakes_count: items.len() as u32,
+            fee_lamports_incoming,
+            msol_fees,
+            total_active_balance: self.state.validator_system.total_active_balance,
+            msol_price_change,
+            reward_fee_used: self.state.reward_fee,
+            total_virtual_staked_lamports,
+            msol_supply,
+        });
+        Ok(())
+    }
+
+    fn check_update_active(&self) -> Result<()> {
         require!(!self.state.paused, MarinadeError::ProgramIsPaused);
         // It is violating the invariants if the delinquent upgrader is currently iterating validators
         require!(
             !self.state.delinquent_upgrader.is_iterating_validators(),
             MarinadeError::DelinquentUpgraderIsNotDone
         );
+        Ok(())
+    }
 
+    // update a single stake account. When deferred_fee_lamports is set (batch update)
+    // the incoming lamports are accumulated into it instead of minting the protocol fees
+    // and the mSOL price is left for the caller to update
+    fn update_stake(
+        &mut self,
+        stake_index: u32,
+        validator_index: u32,
+        mut deferred_fee_lamports: Option<&mut u64>,
+    ) -> Result<()> {
         let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
         let msol_supply = self.state.msol_supply;
         let BeginOutput {
+            stake_index,
             mut stake,
-            mut validator,
             is_treasury_msol_ready_for_transfer,
-        } = self.begin(stake_index, validator_index)?;
+        } = self.begin(stake_index)?;
+        let (validator_index, mut validator) = self.get_validator(validator_index)?;
+        // fees are minted by the caller for the whole batch
+        let mint_fees_now = is_treasury_msol_ready_for_transfer && deferred_fee_lamports.is_none();
 
         let delegation = self.stake_account.delegation().ok_or_else(|| {
             error!(MarinadeError::RequiredDelegatedStake).with_account_name("stake_account")
@@ -329,6 +429,7 @@ impl<'info> UpdateActive<'info> {
         let total_active_balance = self.state.validator_system.total_active_balance;
 
         // require stake is active (deactivation_epoch == u64::MAX)
+        // stakes deactivated by the stake program are fixed by reconcile_deactivated_stake
         require_eq!(
             delegation.deactivation_epoch,
             std::u64::MAX,
@@ -343,6 +444,23 @@ impl<'info> UpdateActive<'info> {
 
         self.delinquent_upgrade(&mut stake, &mut validator)?;
 
+        if stake.last_update_status == StakeStatus::Activating {
+            let (effective_stake, activating_stake) = get_stake_effective_and_activating(
+                &delegation,
+                self.clock.epoch,
+                &self.stake_history,
+                self.state.new_warmup_cooldown_rate_epoch,
+            )?;
+            msg!(
+                "Effective stake {} activating {}",
+                effective_stake,
+                activating_stake
+            );
+            if activating_stake == 0 {
+                stake.last_update_status = StakeStatus::Active;
+            }
+        }
+
         // current lamports amount, to compare with previous
         let delegated_lamports = delegation.stake;
 
@@ -357,14 +475,17 @@ impl<'info> UpdateActive<'info> {
             // by withdrawing to reserve, we add to the SOL assets under control,
             // and by that we increase the mSOL price
             self.withdraw_to_reserve(extra_lamports)?;
+            if let Some(deferred_fee_lamports) = deferred_fee_lamports.as_deref_mut() {
+                *deferred_fee_lamports += extra_lamports;
+            }
             // after sending to reserve, we take protocol_fees as minted mSOL
-            if is_treasury_msol_ready_for_transfer {
+            if mint_fees_now {
                 Some(self.mint_protocol_fees(extra_lamports)?)
             } else {
                 None
             }
         } else {
-            if is_treasury_msol_ready_for_transfer {
+            if mint_fees_now {
                 Some(0)
             } else {
                 None
@@ -378,7 +499,10 @@ impl<'info> UpdateActive<'info> {
                 let rewards = delegated_lamports - stake.last_update_delegated_lamports;
                 msg!("Staking rewards: {}", rewards);
 
-                let delegation_growth_msol_fees = if is_treasury_msol_ready_for_transfer {
+                if let Some(deferred_fee_lamports) = deferred_fee_lamports.as_deref_mut() {
+                    *deferred_fee_lamports += rewards;
+                }
+                let delegation_growth_msol_fees = if mint_fees_now {
                     Some(self.mint_protocol_fees(rewards)?)
                 } else {
                     None
@@ -398,13 +522,15 @@ impl<'info> UpdateActive<'info> {
                 validator.active_balance -= slashed;
                 self.state.validator_system.total_active_balance -= slashed;
                 self.delinquent_upgrade_on_slash(&mut validator, slashed)?;
-                if is_treasury_msol_ready_for_transfer {
+                if mint_fees_now {
                     Some(0)
                 } else {
                     None
                 }
             };
 
+        self.track_validator_rewards(&stake, &delegation, &mut validator, extra_lamports)?;
+
         // mark stake-account as visited
         stake.last_update_epoch = self.clock.epoch;
         let delegation_change = {
@@ -429,7 +555,14 @@ impl<'info> UpdateActive<'info> {
         )?;
 
         // set new mSOL price
-        let msol_price_change = self.update_msol_price()?;
+        let msol_price_change = if deferred_fee_lamports.is_none() {
+            self.update_msol_price()?
+        } else {
+            U64ValueChange {
+                old: self.state.msol_price,
+                new: self.state.msol_price,
+            }
+        };
         // save stake record
         self.state.stake_system.set(
             &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
@@ -465,6 +598,41 @@ impl<'info> UpdateActive<'info> {
         Ok(())
     }
 
+    // accumulate realized and expected rewards of the validator for the bond settlement
+    // and the per-validator rewards attribution. Must be called before the stake record is marked as visited
+    fn track_validator_rewards(
+        &self,
+        stake: &StakeRecord,
+        delegation: &Delegation,
+        validator: &mut ValidatorRecord,
+        extra_lamports: u64,
+    ) -> Result<()> {
+        validator.roll_rewards_epoch(self.clock.epoch);
+        let rewards = delegation
+            .stake
+            .saturating_sub(stake.last_update_delegated_lamports);
+        validator.epoch_rewards += rewards;
+        validator.epoch_extra_lamports += extra_lamports;
+        validator.total_rewards += rewards;
+        validator.total_extra_lamports += extra_lamports;
+        // rewards are expected only for the stake effective during the previous epoch
+        // when the last update was done in the previous epoch. A stake created by stake_reserve
+        // is not effective in the epoch it was created in and may take more epochs to warm up
+        if stake.last_update_epoch + 1 == self.clock.epoch {
+            let (effective_stake, _) = get_stake_effective_and_activating(
+                delegation,
+                stake.last_update_epoch,
+                &self.stake_history,
+                self.state.new_warmup_cooldown_rate_epoch,
+            )?;
+            validator.epoch_expected_rewards += self
+                .state
+                .expected_epoch_reward_rate
+                .apply(effective_stake.min(stake.last_update_delegated_lamports));
+        }
+        Ok(())
+    }
+
     // helper fn to upgrade the data from Unknown to Active and advance the iteration
     // this is done only on the first loop after program upgrade (first staged of delinquent stake iterator)
     fn delinquent_upgrade(
@@ -552,10 +720,10 @@ impl<'info> UpdateDeactivated<'info> {
         let msol_supply = self.state.msol_supply;
         let operational_sol_balance = self.operational_sol_account.lamports();
         let BeginOutput {
+            stake_index,
             mut stake,
-            mut validator,
             is_treasury_msol_ready_for_transfer,
-        } = self.begin(stake_index, validator_index)?;
+        } = self.begin(stake_index)?;
 
         let delegation = self.stake_account.delegation().ok_or_else(|| {
             error!(MarinadeError::RequiredDelegatedStake).with_account_name("stake_account")
@@ -566,13 +734,14 @@ impl<'info> UpdateDeactivated<'info> {
             std::u64::MAX,
             MarinadeError::RequiredDeactivatingStake
         );
-        if stake.last_update_status == StakeStatus::Active {
+        if stake.last_update_status.is_active_or_activating() {
             // Detected deactivation of delinquent stake-account
             // applying emergency unstake procedure before processing the stake deletion
             require!(
                 !stake.is_emergency_unstaking,
                 MarinadeError::StakeAccountIsEmergencyUnstaking
             );
+            let (validator_index, mut validator) = self.get_validator(validator_index)?;
             stake.is_emergency_unstaking = true;
             self.state.emergency_cooling_down += stake.last_update_delegated_lamports;
             self.state.validator_system.total_active_balance -=
@@ -628,6 +797,18 @@ impl<'info> UpdateDeactivated<'info> {
             }
         };
 
+        // the stake program does not allow to withdraw the stake while it is cooling down
+        let cooling_down_stake = get_stake_effective_deactivating(
+            &delegation,
+            self.clock.epoch,
+            &self.stake_history,
+            self.state.new_warmup_cooldown_rate_epoch,
+        )?;
+        if cooling_down_stake > 0 {
+            msg!("Stake {} is still cooling down", cooling_down_stake);
+            return err!(MarinadeError::StakeStillCoolingDown);
+        }
+
         // withdraw all to reserve (the stake account will be marked for deletion by the system)
         self.common
             .withdraw_to_reserve(self.stake_account.to_account_info().lamports())?;
@@ -93,12 +93,13 @@ impl<'info> OrderUnstake<'info> {
         self.state.on_msol_burn(msol_amount);
 
         // initialize new_ticket_account
-        let created_epoch = self.clock.epoch
-            + if self.clock.epoch == self.state.stake_system.last_stake_delta_epoch {
-                1
-            } else {
-                0
-            };
+        // once the stake-delta of the epoch has started the unstake for this ticket
+        // may only happen in the next epoch, so the ticket waits one more epoch.
+        // Early stake_reserve runs before the window do not set last_stake_delta_epoch
+        // (see early_stake_delta_threshold)
+        let is_stake_delta_started =
+            self.clock.epoch == self.state.stake_system.last_stake_delta_epoch;
+        let created_epoch = self.clock.epoch + if is_stake_delta_started { 1 } else { 0 };
         self.new_ticket_account.set_inner(TicketAccountData {
             state_address: self.state.key(),
             beneficiary: ticket_beneficiary,
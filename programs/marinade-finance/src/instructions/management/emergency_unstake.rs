@@ -15,11 +15,12 @@ use anchor_spl::stake::{deactivate_stake, DeactivateStake, Stake, StakeAccount};
 pub struct EmergencyUnstake<'info> {
     #[account(mut)]
     pub state: Account<'info, State>,
+    /// CHECK: must sign unless the validator is marked as delinquent, for removal or migrated
     #[account(
         address = state.validator_system.manager_authority
             @ MarinadeError::InvalidValidatorManager
     )]
-    pub validator_manager_authority: Signer<'info>,
+    pub validator_manager_authority: UncheckedAccount<'info>,
     #[account(
         mut,
         address = state.validator_system.validator_list.account,
@@ -45,6 +46,10 @@ pub struct EmergencyUnstake<'info> {
     pub clock: Sysvar<'info, Clock>,
 
     pub stake_program: Program<'info, Stake>,
+
+    // appended so the accounts of the existing clients keep their positions
+    /// CHECK: parsed in code, the delinquency is checked again before unstaking without the manager signature
+    pub validator_vote: UncheckedAccount<'info>,
 }
 
 impl<'info> EmergencyUnstake<'info> {
@@ -55,15 +60,16 @@ impl<'info> EmergencyUnstake<'info> {
             MarinadeError::DelinquentUpgraderIsNotDone
         );
 
-        let mut stake = self.state.stake_system.get_checked(
+        // the indexes are hints, the records are looked up by the account keys if they were moved
+        let (stake_index, mut stake) = self.state.stake_system.get_by_key(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             stake_index,
             self.stake_account.to_account_info().key,
         )?;
 
-        require_eq!(
-            stake.last_update_status,
-            StakeStatus::Active,
+        // activating stakes can be deactivated too
+        require!(
+            stake.last_update_status.is_active_or_activating(),
             MarinadeError::RequiredActiveStake
         );
         // check the account is not already in emergency_unstake
@@ -72,17 +78,33 @@ impl<'info> EmergencyUnstake<'info> {
             MarinadeError::StakeAccountIsEmergencyUnstaking
         );
 
-        let mut validator = self.state.validator_system.get(
+        let (validator_index, mut validator) = self.state.validator_system.get_by_key(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
+            self.validator_vote.key,
         )?;
 
-        // One more level of protection: need to run setScore(0) before this. I don't know is it really a good idea
-        require_eq!(
-            validator.score,
-            0,
-            MarinadeError::EmergencyUnstakingFromNonZeroScoredValidator
-        );
+        // stakes of validators marked for removal by the manager or marked as delinquent
+        // by check_validator_delinquency and still delinquent can be unstaked by anyone
+        let is_delinquent = validator.delinquent
+            && self
+                .state
+                .is_vote_account_delinquent(&self.validator_vote, &self.clock);
+        // and the stakes of migrated validators when they can't be redelegated to the new vote account
+        let is_migration =
+            validator.migrated_to != Pubkey::default() && !self.state.native_redelegate_enabled;
+        if !validator.marked_for_removal && !is_delinquent && !is_migration {
+            require!(
+                self.validator_manager_authority.is_signer,
+                MarinadeError::InvalidValidatorManager
+            );
+            // One more level of protection: need to run setScore(0) before this. I don't know is it really a good idea
+            require_eq!(
+                validator.score,
+                0,
+                MarinadeError::EmergencyUnstakingFromNonZeroScoredValidator
+            );
+        }
 
         // check that the account is delegated to the right validator
         check_stake_amount_and_validator(
@@ -92,7 +114,10 @@ impl<'info> EmergencyUnstake<'info> {
         )?;
 
         let unstake_amount = stake.last_update_delegated_lamports;
-        self.state.on_stake_moved(unstake_amount, &self.clock)?;
+        // like the redelegate of a migration, not counted as stake moved
+        if !is_migration {
+            self.state.on_stake_moved(unstake_amount, &self.clock)?;
+        }
         msg!("Deactivate whole stake {}", stake.stake_account);
         deactivate_stake(CpiContext::new_with_signer(
             self.stake_program.to_account_info(),
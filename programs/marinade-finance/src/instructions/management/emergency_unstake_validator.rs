@@ -0,0 +1,196 @@
+use crate::{
+    checks::check_stake_amount_and_validator,
+    error::MarinadeError,
+    events::management::EmergencyUnstakeValidatorEvent,
+    require_lte,
+    state::{
+        stake_system::{StakeList, StakeStatus, StakeSystem},
+        validator_system::ValidatorList,
+    },
+    State,
+};
+
+use anchor_lang::prelude::*;
+use anchor_spl::stake::{deactivate_stake, DeactivateStake, Stake, StakeAccount};
+
+#[derive(Accounts)]
+pub struct EmergencyUnstakeValidator<'info> {
+    #[account(mut)]
+    pub state: Box<Account<'info, State>>,
+    /// validator manager or pause authority
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    /// CHECK: parsed in code, the delinquency is checked again before skipping the score check
+    pub validator_vote: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_DEPOSIT_SEED
+        ],
+        bump = state.stake_system.stake_deposit_bump_seed
+    )]
+    pub stake_deposit_authority: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+
+    pub stake_program: Program<'info, Stake>,
+}
+
+impl<'info> EmergencyUnstakeValidator<'info> {
+    /// Deactivates all the passed stake accounts of the validator at once.
+    /// stake_indexes[i] is the stake list index hint of the remaining account i.
+    /// The pause authority is not limited by max_stake_moved_per_epoch,
+    /// doesn't need to set the validator score to 0 first and can unstake while paused
+    //
+    // fn emergency_unstake_validator()
+    pub fn process(
+        &mut self,
+        validator_index: u32,
+        stake_indexes: Vec<u32>,
+        stake_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(
+            self.state.delinquent_upgrader.is_done(),
+            MarinadeError::DelinquentUpgraderIsNotDone
+        );
+        require_eq!(
+            stake_indexes.len(),
+            stake_accounts.len(),
+            MarinadeError::BatchAccountsMismatch
+        );
+        require_lte!(
+            stake_accounts.len(),
+            self.state.stake_system.stake_count() as usize,
+            MarinadeError::BatchAccountsMismatch
+        );
+
+        let is_pause_authority = self.authority.key() == self.state.pause_authority;
+        if !is_pause_authority {
+            // the pause authority can still unstake from a validator while the program is paused
+            require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+            require_keys_eq!(
+                self.authority.key(),
+                self.state.validator_system.manager_authority,
+                MarinadeError::InvalidValidatorManager
+            );
+        }
+
+        // the indexes are hints, the records are looked up by the account keys if they were moved
+        let (validator_index, mut validator) = self.state.validator_system.get_by_key(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            self.validator_vote.key,
+        )?;
+        // the delinquent mark may be stale, the validator could have voted since
+        let is_delinquent = validator.delinquent
+            && self
+                .state
+                .is_vote_account_delinquent(&self.validator_vote, &self.clock);
+        if !is_pause_authority && !validator.marked_for_removal && !is_delinquent {
+            // same protection as emergency_unstake
+            require_eq!(
+                validator.score,
+                0,
+                MarinadeError::EmergencyUnstakingFromNonZeroScoredValidator
+            );
+        }
+
+        let mut unstaked_accounts = Vec::with_capacity(stake_accounts.len());
+        let mut total_unstake_amount: u64 = 0;
+        for (stake_index, stake_account_info) in stake_indexes.into_iter().zip(stake_accounts) {
+            let stake_account: Account<StakeAccount> = Account::try_from(stake_account_info)?;
+            let (stake_index, mut stake) = self.state.stake_system.get_by_key(
+                &self.stake_list.to_account_info().data.as_ref().borrow(),
+                stake_index,
+                stake_account_info.key,
+            )?;
+            require!(
+                stake.last_update_status.is_active_or_activating(),
+                MarinadeError::RequiredActiveStake
+            );
+            require!(
+                !stake.is_emergency_unstaking,
+                MarinadeError::StakeAccountIsEmergencyUnstaking
+            );
+            check_stake_amount_and_validator(
+                &stake_account,
+                stake.last_update_delegated_lamports,
+                &validator.validator_account,
+            )?;
+
+            msg!("Deactivate whole stake {}", stake.stake_account);
+            deactivate_stake(CpiContext::new_with_signer(
+                self.stake_program.to_account_info(),
+                DeactivateStake {
+                    stake: stake_account_info.clone(),
+                    staker: self.stake_deposit_authority.to_account_info(),
+                    clock: self.clock.to_account_info(),
+                },
+                &[&[
+                    &self.state.key().to_bytes(),
+                    StakeSystem::STAKE_DEPOSIT_SEED,
+                    &[self.state.stake_system.stake_deposit_bump_seed],
+                ]],
+            ))?;
+
+            total_unstake_amount += stake.last_update_delegated_lamports;
+            stake.is_emergency_unstaking = true;
+            stake.last_update_status = StakeStatus::Deactivating;
+            self.state.stake_system.set(
+                &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+                stake_index,
+                stake,
+            )?;
+            unstaked_accounts.push(stake.stake_account);
+        }
+
+        if is_pause_authority {
+            self.state
+                .on_stake_moved_uncapped(total_unstake_amount, &self.clock);
+        } else {
+            self.state
+                .on_stake_moved(total_unstake_amount, &self.clock)?;
+        }
+
+        // the whole amount is no longer "active" for this validator
+        validator.active_balance -= total_unstake_amount;
+        // move from total_active_balance -> total_cooling_down
+        self.state.validator_system.total_active_balance -= total_unstake_amount;
+        self.state.emergency_cooling_down += total_unstake_amount;
+
+        self.state.validator_system.set(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+
+        emit!(EmergencyUnstakeValidatorEvent {
+            state: self.state.key(),
+            validator: validator.validator_account,
+            index: validator_index,
+            authority: self.authority.key(),
+            is_pause_authority,
+            unstaked_accounts,
+            total_unstake_amount,
+            active_balance_left: validator.active_balance,
+            emergency_cooling_down: self.state.emergency_cooling_down,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::checks::get_vote_account_node_pubkey;
+use crate::events::management::InitValidatorBondEvent;
+use crate::state::validator_bond::ValidatorBond;
+use crate::{error::MarinadeError, State};
+
+#[derive(Accounts)]
+pub struct InitValidatorBond<'info> {
+    pub state: Account<'info, State>,
+
+    /// CHECK: parsed in code
+    pub validator_vote: UncheckedAccount<'info>,
+    /// the node identity of the vote account, pays the rent and the bond
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub validator_identity: Signer<'info>,
+
+    #[account(
+        init,
+        payer = validator_identity,
+        space = ValidatorBond::serialized_len(),
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorBond::SEED,
+            &validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub validator_bond: Account<'info, ValidatorBond>,
+
+    pub clock: Sysvar<'info, Clock>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitValidatorBond<'info> {
+    // the bond can be topped up later by a plain transfer to the bond account
+    pub fn process(&mut self, bond_lamports: u64, bond_bump: u8) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        require_keys_eq!(
+            get_vote_account_node_pubkey(&self.validator_vote)?,
+            self.validator_identity.key(),
+            MarinadeError::WrongValidatorIdentity
+        );
+
+        self.validator_bond.set_inner(ValidatorBond {
+            state: self.state.key(),
+            validator_vote: self.validator_vote.key(),
+            authority: self.validator_identity.key(),
+            bump: bond_bump,
+            // the bond does not cover rewards observed before it was created
+            last_settled_epoch: self.clock.epoch,
+            withdraw_requested_epoch: ValidatorBond::NO_WITHDRAW_REQUEST,
+        });
+        transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.validator_identity.to_account_info(),
+                    to: self.validator_bond.to_account_info(),
+                },
+            ),
+            bond_lamports,
+        )?;
+
+        emit!(InitValidatorBondEvent {
+            state: self.state.key(),
+            validator: self.validator_vote.key(),
+            authority: self.validator_identity.key(),
+            bond_lamports,
+        });
+
+        Ok(())
+    }
+}
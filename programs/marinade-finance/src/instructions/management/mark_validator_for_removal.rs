@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarinadeError, events::management::MarkValidatorForRemovalEvent,
+    state::validator_system::ValidatorList, State,
+};
+
+#[derive(Accounts)]
+pub struct MarkValidatorForRemoval<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ MarinadeError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+}
+
+impl<'info> MarkValidatorForRemoval<'info> {
+    /// Zeroes the validator target and lets anyone emergency_unstake its stakes
+    /// and remove_validator once its active balance is 0
+    pub fn process(&mut self, index: u32, validator_vote: Pubkey) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+
+        let mut validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            &validator_vote,
+        )?;
+
+        // the capped stake is released like in remove_validator
+        self.state
+            .update_validator_eligibility(&mut validator, |validator| {
+                validator.marked_for_removal = true
+            });
+        self.state.validator_system.set(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            index,
+            validator,
+        )?;
+
+        emit!(MarkValidatorForRemovalEvent {
+            state: self.state.key(),
+            validator: validator_vote,
+            index,
+            active_balance: validator.active_balance,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+
+use crate::checks::get_vote_account_node_pubkey;
+use crate::events::management::MigrateValidatorEvent;
+use crate::state::validator_system::{ValidatorList, ValidatorRecord};
+use crate::{error::MarinadeError, State};
+
+#[derive(Accounts)]
+pub struct MigrateValidator<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ MarinadeError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+
+    /// CHECK: parsed in code, the vote account of the migrated record
+    pub old_validator_vote: UncheckedAccount<'info>,
+    /// CHECK: parsed in code, must have the node identity of the old vote account
+    pub new_validator_vote: UncheckedAccount<'info>,
+
+    /// CHECK: no discriminator used
+    /// by initializing this account we mark the new vote account as added
+    #[account(
+        init, // will ensure it is system account
+        payer = rent_payer,
+        space = 0,
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorRecord::DUPLICATE_FLAG_SEED,
+            &new_validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub new_duplication_flag: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub rent_payer: Signer<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateValidator<'info> {
+    /// Adds a record for the new vote account keeping the score, caps and rewards history of the old one.
+    /// The old record is linked to the new one (migrated_to), so its stake target is 0.
+    /// Its stakes are moved by anyone with redelegate when native_redelegate_enabled is set,
+    /// otherwise they are emergency unstaked by anyone and stake_reserve delegates the SOL
+    /// to the new vote account. deactivate_stake unstakes them too on a negative stake delta
+    pub fn process(&mut self, index: u32) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        require!(
+            self.state.delinquent_upgrader.is_done(),
+            MarinadeError::DelinquentUpgraderIsNotDone
+        );
+
+        let old_validator_vote = self.old_validator_vote.key();
+        let mut old_validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            &old_validator_vote,
+        )?;
+        require_keys_eq!(
+            old_validator.migrated_to,
+            Pubkey::default(),
+            MarinadeError::ValidatorAlreadyMigrated
+        );
+        // the same validator node with a new vote account
+        require_keys_eq!(
+            get_vote_account_node_pubkey(&self.new_validator_vote)?,
+            get_vote_account_node_pubkey(&self.old_validator_vote)?,
+            MarinadeError::WrongValidatorIdentity
+        );
+
+        msg!(
+            "Migrate validator {} to {}",
+            old_validator_vote,
+            self.new_validator_vote.key
+        );
+
+        let mut new_validator = ValidatorRecord::new(
+            self.new_validator_vote.key(),
+            old_validator.score,
+            &self.state.key(),
+            self.new_duplication_flag.key,
+        )?;
+        new_validator.stake_cap = old_validator.stake_cap;
+        new_validator.rewards_epoch = old_validator.rewards_epoch;
+        new_validator.epoch_rewards = old_validator.epoch_rewards;
+        new_validator.epoch_expected_rewards = old_validator.epoch_expected_rewards;
+        new_validator.last_rewards_epoch = old_validator.last_rewards_epoch;
+        new_validator.last_epoch_rewards = old_validator.last_epoch_rewards;
+        new_validator.last_epoch_expected_rewards = old_validator.last_epoch_expected_rewards;
+        new_validator.epoch_extra_lamports = old_validator.epoch_extra_lamports;
+        new_validator.last_epoch_extra_lamports = old_validator.last_epoch_extra_lamports;
+        new_validator.total_rewards = old_validator.total_rewards;
+        new_validator.total_extra_lamports = old_validator.total_extra_lamports;
+        // a scheduled score moves to the new record, pending_scores_count does not change
+        new_validator.pending_score = old_validator.pending_score;
+        new_validator.pending_score_epoch = old_validator.pending_score_epoch;
+        old_validator.pending_score = 0;
+        old_validator.pending_score_epoch = 0;
+
+        // the new record is not capped until checked by check_validator_stake_cap
+        let new_validator_vote = new_validator.validator_account;
+        self.state
+            .update_validator_eligibility(&mut old_validator, |validator| {
+                validator.migrated_to = new_validator_vote
+            });
+        self.state.validator_system.set(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            index,
+            old_validator,
+        )?;
+
+        self.state.validator_system.add_record(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            new_validator,
+        )?;
+
+        emit!(MigrateValidatorEvent {
+            state: self.state.key(),
+            old_validator: old_validator_vote,
+            old_index: index,
+            new_validator: new_validator.validator_account,
+            new_index: self.state.validator_system.validator_count() - 1,
+            score: new_validator.score,
+        });
+
+        Ok(())
+    }
+}
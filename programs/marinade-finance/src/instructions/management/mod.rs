@@ -1,11 +1,35 @@
 pub mod add_validator;
 pub mod emergency_unstake;
+pub mod emergency_unstake_validator;
+pub mod init_validator_bond;
+pub mod mark_validator_for_removal;
+pub mod migrate_validator;
 pub mod partial_unstake;
+pub mod redelegate;
+pub mod register_validator;
 pub mod remove_validator;
+pub mod request_validator_bond_withdrawal;
+pub mod schedule_validator_scores;
 pub mod set_validator_score;
+pub mod set_validator_scores;
+pub mod set_validator_stake_cap;
+pub mod split_oversized_stake;
+pub mod withdraw_validator_bond;
 
 pub use add_validator::*;
 pub use emergency_unstake::*;
+pub use emergency_unstake_validator::*;
+pub use init_validator_bond::*;
+pub use mark_validator_for_removal::*;
+pub use migrate_validator::*;
 pub use partial_unstake::*;
+pub use redelegate::*;
+pub use register_validator::*;
 pub use remove_validator::*;
+pub use request_validator_bond_withdrawal::*;
+pub use schedule_validator_scores::*;
 pub use set_validator_score::*;
+pub use set_validator_scores::*;
+pub use set_validator_stake_cap::*;
+pub use split_oversized_stake::*;
+pub use withdraw_validator_bond::*;
@@ -97,20 +97,26 @@ impl<'info> PartialUnstake<'info> {
             "desired_unstake_amount too low"
         );
 
-        let mut validator = self.state.validator_system.get(
+        // the indexes are hints, the records are looked up by the account keys if they were moved
+        let (validator_index, mut validator) = self.state.validator_system.get_by_key(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
+            &self
+                .stake_account
+                .delegation()
+                .ok_or(error!(MarinadeError::StakeNotDelegated))?
+                .voter_pubkey,
         )?;
 
-        let mut stake = self.state.stake_system.get_checked(
+        let (stake_index, mut stake) = self.state.stake_system.get_by_key(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             stake_index,
             self.stake_account.to_account_info().key,
         )?;
 
-        require_eq!(
-            stake.last_update_status,
-            StakeStatus::Active,
+        // activating stakes can be deactivated too
+        require!(
+            stake.last_update_status.is_active_or_activating(),
             MarinadeError::RequiredActiveStake
         );
         // check the account is not already in emergency_unstake
@@ -120,7 +126,7 @@ impl<'info> PartialUnstake<'info> {
         );
 
         // check amount currently_staked in this account
-        // and that the account is delegated to the validator_index sent
+        // and that the account is delegated to the validator
         check_stake_amount_and_validator(
             &self.stake_account,
             stake.last_update_delegated_lamports,
@@ -136,17 +142,24 @@ impl<'info> PartialUnstake<'info> {
         let total_stake_target =
             u64::try_from(total_stake_target_i128).expect("total_stake_target+stake_delta");
         // compute target for this particular validator (total_stake_target * score/total_score)
+        // over-cap validators are over target
         let validator_stake_target = self
             .state
-            .validator_system
             .validator_stake_target(&validator, total_stake_target)?;
-        // if validator is already on-target (or the split will be lower than min_stake), exit now
-        if validator.active_balance <= validator_stake_target + self.state.stake_system.min_stake {
+        // if validator is already on-target, within the hysteresis band when no tickets are waiting
+        // for the stake (or the split will be lower than min_stake), exit now
+        if validator.active_balance
+            <= self
+                .state
+                .validator_unstake_bound(validator_stake_target, total_stake_delta_i128)
+                + self.state.stake_system.min_stake
+        {
             msg!(
-                "Current validator {} stake {} is <= target {} +min_stake",
+                "Current validator {} stake {} is <= target {} (hysteresis {}) +min_stake",
                 validator.validator_account,
                 validator.active_balance,
-                validator_stake_target
+                validator_stake_target,
+                self.state.stake_target_hysteresis
             );
             self.return_unused_split_stake_account_rent()?;
             return Ok(()); // Not an error. Don't fail other instructions in tx
@@ -207,8 +220,8 @@ impl<'info> PartialUnstake<'info> {
                 &self.split_stake_account.key(),
                 unstake_amount,
                 &self.clock,
-                true,  // is_emergency_unstaking
-                false, // is_active
+                true, // is_emergency_unstaking
+                StakeStatus::Deactivating,
             )?;
 
             // split & deactivate stake account
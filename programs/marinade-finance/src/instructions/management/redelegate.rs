@@ -0,0 +1,426 @@
+use crate::{
+    checks::check_stake_amount_and_validator,
+    error::MarinadeError,
+    events::{crank::SplitStakeAccountInfo, management::RedelegateEvent},
+    require_lte,
+    state::{
+        stake_system::{StakeList, StakeStatus, StakeSystem},
+        validator_system::ValidatorList,
+    },
+    State,
+};
+use std::convert::TryFrom;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    program::invoke_signed,
+    stake::{self, state::StakeState},
+    system_program,
+    sysvar::stake_history,
+};
+use anchor_spl::stake::{withdraw, Stake, StakeAccount, Withdraw};
+
+#[derive(Accounts)]
+pub struct Redelegate<'info> {
+    #[account(mut)]
+    pub state: Box<Account<'info, State>>,
+    /// CHECK: must sign unless moving stake of a migrated validator to its new vote account
+    #[account(
+        address = state.validator_system.manager_authority
+            @ MarinadeError::InvalidValidatorManager
+    )]
+    pub validator_manager_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+    #[account(mut)]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_DEPOSIT_SEED
+        ],
+        bump = state.stake_system.stake_deposit_bump_seed
+    )]
+    pub stake_deposit_authority: UncheckedAccount<'info>,
+    // Readonly. For stake delta calculation
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            State::RESERVE_SEED
+        ],
+        bump = state.reserve_bump_seed
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    /// used only when part of the stake account is redelegated
+    #[account(
+        init,
+        payer = stake_rent_payer,
+        space = std::mem::size_of::<StakeState>(),
+        owner = stake::program::ID,
+    )]
+    pub split_stake_account: Account<'info, StakeAccount>,
+    /// receives the redelegated stake
+    #[account(
+        init,
+        payer = stake_rent_payer,
+        space = std::mem::size_of::<StakeState>(),
+        owner = stake::program::ID,
+    )]
+    pub redelegate_stake_account: Account<'info, StakeAccount>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub stake_rent_payer: Signer<'info>,
+
+    /// CHECK: CPI
+    pub dest_validator_account: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: have no CPU budget to parse
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: CPI
+    #[account(address = stake::config::ID)]
+    pub stake_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub stake_program: Program<'info, Stake>,
+}
+
+impl<'info> Redelegate<'info> {
+    /// Moves stake from an over-target validator to an under-target one without deactivation.
+    /// The native redelegate leaves the source account deactivating (StakeStatus::Redelegating)
+    /// with only its rent and rewards, it is cleaned by update_deactivated.
+    /// Only where the stake program redelegate is enabled (native_redelegate_enabled), elsewhere
+    /// stake moves by deactivate_stake of over-target validators and stake_reserve of under-target ones
+    pub fn process(
+        &mut self,
+        stake_index: u32,
+        source_validator_index: u32,
+        dest_validator_index: u32,
+        desired_redelegate_amount: u64,
+    ) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        require!(
+            self.state.native_redelegate_enabled,
+            MarinadeError::NativeRedelegateIsNotEnabled
+        );
+        require!(
+            self.state.delinquent_upgrader.is_done(),
+            MarinadeError::DelinquentUpgraderIsNotDone
+        );
+        require_gte!(
+            desired_redelegate_amount,
+            self.state.stake_system.min_stake,
+            MarinadeError::TooLowDelegationInDepositingStake
+        );
+
+        // the indexes are hints, the records are looked up by the account keys if they were moved
+        let (stake_index, mut stake) = self.state.stake_system.get_by_key(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
+        require_eq!(
+            stake.last_update_status,
+            StakeStatus::Active,
+            MarinadeError::RequiredActiveStake
+        );
+        require!(
+            !stake.is_emergency_unstaking,
+            MarinadeError::StakeAccountIsEmergencyUnstaking
+        );
+
+        let (source_validator_index, mut source_validator) =
+            self.state.validator_system.get_by_key(
+                &self.validator_list.to_account_info().data.as_ref().borrow(),
+                source_validator_index,
+                &self
+                    .stake_account
+                    .delegation()
+                    .ok_or(error!(MarinadeError::StakeNotDelegated))?
+                    .voter_pubkey,
+            )?;
+        let (dest_validator_index, mut dest_validator) = self.state.validator_system.get_by_key(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            dest_validator_index,
+            self.dest_validator_account.key,
+        )?;
+        require_neq!(
+            source_validator_index,
+            dest_validator_index,
+            MarinadeError::SourceAndDestValidatorsAreTheSame
+        );
+        // moving stakes of a migrated validator to its new vote account is permissionless
+        // and it is not limited by the dest target nor counted as stake moved
+        let is_migration = source_validator.migrated_to != Pubkey::default()
+            && source_validator.migrated_to == dest_validator.validator_account;
+        if !is_migration {
+            require!(
+                self.validator_manager_authority.is_signer,
+                MarinadeError::InvalidValidatorManager
+            );
+        }
+        // record for event
+        let source_validator_balance = source_validator.active_balance;
+        let dest_validator_balance = dest_validator.active_balance;
+        let last_update_delegation = stake.last_update_delegated_lamports;
+
+        // check amount currently_staked in this account
+        // and that the account is delegated to the source validator
+        check_stake_amount_and_validator(
+            &self.stake_account,
+            stake.last_update_delegated_lamports,
+            &source_validator.validator_account,
+        )?;
+
+        // compute total target stake (current total active stake +/- delta)
+        let total_stake_target = u64::try_from(
+            self.state.validator_system.total_active_balance as i128
+                + self.state.stake_delta(self.reserve_pda.lamports()),
+        )
+        .expect("total_stake_target+stake_delta");
+        let source_validator_stake_target = self
+            .state
+            .validator_stake_target(&source_validator, total_stake_target)?;
+        let dest_validator_stake_target = self
+            .state
+            .validator_stake_target(&dest_validator, total_stake_target)?;
+
+        // can not move more than the source is over its target
+        let redelegate_amount = desired_redelegate_amount.min(
+            source_validator
+                .active_balance
+                .saturating_sub(source_validator_stake_target),
+        );
+        // whole account if the remainder would be lower than min_stake
+        let redelegate_amount = if stake
+            .last_update_delegated_lamports
+            .saturating_sub(redelegate_amount)
+            < self.state.stake_system.min_stake
+        {
+            stake.last_update_delegated_lamports
+        } else {
+            redelegate_amount
+        };
+        if redelegate_amount < self.state.stake_system.min_stake {
+            msg!(
+                "Source validator {} stake {} is not over target {} by min_stake",
+                source_validator.validator_account,
+                source_validator.active_balance,
+                source_validator_stake_target
+            );
+            self.return_unused_stake_account_rent(&self.split_stake_account)?;
+            self.return_unused_stake_account_rent(&self.redelegate_stake_account)?;
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+        if !is_migration {
+            require_lte!(
+                dest_validator.active_balance + redelegate_amount,
+                dest_validator_stake_target,
+                MarinadeError::RedelegateOverTarget
+            );
+            self.state.on_stake_moved(redelegate_amount, &self.clock)?;
+        }
+
+        let split_stake_account = if redelegate_amount < stake.last_update_delegated_lamports {
+            msg!(
+                "Split {} ({} lamports) from stake {}",
+                self.split_stake_account.key(),
+                redelegate_amount,
+                stake.stake_account
+            );
+            let split_instruction = stake::instruction::split(
+                self.stake_account.to_account_info().key,
+                self.stake_deposit_authority.key,
+                redelegate_amount,
+                &self.split_stake_account.key(),
+            )
+            .last()
+            .unwrap()
+            .clone();
+            invoke_signed(
+                &split_instruction,
+                &[
+                    self.stake_program.to_account_info(),
+                    self.stake_account.to_account_info(),
+                    self.split_stake_account.to_account_info(),
+                    self.stake_deposit_authority.to_account_info(),
+                ],
+                &[&[
+                    &self.state.key().to_bytes(),
+                    StakeSystem::STAKE_DEPOSIT_SEED,
+                    &[self.state.stake_system.stake_deposit_bump_seed],
+                ]],
+            )?;
+            stake.last_update_delegated_lamports -= redelegate_amount;
+            self.state.stake_system.set(
+                &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+                stake_index,
+                stake,
+            )?;
+
+            // the split account is the transient one
+            self.state.stake_system.add(
+                &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+                &self.split_stake_account.key(),
+                0,
+                &self.clock,
+                false, // is_emergency_unstaking? no
+                StakeStatus::Deactivating,
+            )?;
+            let split_stake_index = self.state.stake_system.stake_count() - 1;
+            let mut split_stake = self.state.stake_system.get_checked(
+                &self.stake_list.to_account_info().data.as_ref().borrow(),
+                split_stake_index,
+                &self.split_stake_account.key(),
+            )?;
+            split_stake.last_update_status = StakeStatus::Redelegating;
+            self.state.stake_system.set(
+                &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+                split_stake_index,
+                split_stake,
+            )?;
+            Some(SplitStakeAccountInfo {
+                account: self.split_stake_account.key(),
+                index: split_stake_index,
+            })
+        } else {
+            self.return_unused_stake_account_rent(&self.split_stake_account)?;
+            // the whole account is transient, its delegated lamports are moved
+            stake.last_update_delegated_lamports = 0;
+            stake.last_update_status = StakeStatus::Redelegating;
+            self.state.stake_system.set(
+                &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+                stake_index,
+                stake,
+            )?;
+            None
+        };
+        let transient_stake_account = if split_stake_account.is_some() {
+            self.split_stake_account.to_account_info()
+        } else {
+            self.stake_account.to_account_info()
+        };
+
+        msg!(
+            "Redelegate {} lamports to {}",
+            redelegate_amount,
+            self.dest_validator_account.key
+        );
+        let redelegate_instruction = stake::instruction::redelegate(
+            transient_stake_account.key,
+            self.stake_deposit_authority.key,
+            self.dest_validator_account.key,
+            &self.redelegate_stake_account.key(),
+        )
+        .last()
+        .unwrap()
+        .clone();
+        invoke_signed(
+            &redelegate_instruction,
+            &[
+                self.stake_program.to_account_info(),
+                transient_stake_account,
+                self.redelegate_stake_account.to_account_info(),
+                self.dest_validator_account.to_account_info(),
+                self.stake_config.to_account_info(),
+                self.stake_deposit_authority.to_account_info(),
+            ],
+            &[&[
+                &self.state.key().to_bytes(),
+                StakeSystem::STAKE_DEPOSIT_SEED,
+                &[self.state.stake_system.stake_deposit_bump_seed],
+            ]],
+        )?;
+
+        self.state.stake_system.add(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            &self.redelegate_stake_account.key(),
+            redelegate_amount,
+            &self.clock,
+            false, // is_emergency_unstaking? no
+            StakeStatus::Activating,
+        )?;
+
+        // total_active_balance does not change, the stake just moves between validators
+        source_validator.active_balance -= redelegate_amount;
+        dest_validator.active_balance += redelegate_amount;
+        self.state.validator_system.set(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            source_validator_index,
+            source_validator,
+        )?;
+        self.state.validator_system.set(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            dest_validator_index,
+            dest_validator,
+        )?;
+
+        emit!(RedelegateEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            stake_index,
+            stake_account: self.stake_account.key(),
+            last_update_delegation,
+            source_validator_index,
+            source_validator_vote: source_validator.validator_account,
+            source_validator_score: source_validator.score,
+            source_validator_balance,
+            source_validator_stake_target,
+            dest_validator_index,
+            dest_validator_vote: dest_validator.validator_account,
+            dest_validator_score: dest_validator.score,
+            dest_validator_balance,
+            dest_validator_stake_target,
+            redelegate_amount,
+            split_stake_account,
+            redelegate_stake_index: self.state.stake_system.stake_count() - 1,
+            redelegate_stake_account: self.redelegate_stake_account.key(),
+        });
+
+        Ok(())
+    }
+
+    fn return_unused_stake_account_rent(
+        &self,
+        stake_account: &Account<'info, StakeAccount>,
+    ) -> Result<()> {
+        // Return back the rent reserve of unused stake account in case of early return
+        withdraw(
+            CpiContext::new(
+                self.stake_program.to_account_info(),
+                Withdraw {
+                    stake: stake_account.to_account_info(),
+                    withdrawer: stake_account.to_account_info(),
+                    to: self.stake_rent_payer.to_account_info(),
+                    clock: self.clock.to_account_info(),
+                    stake_history: self.stake_history.to_account_info(),
+                },
+            ),
+            stake_account.to_account_info().lamports(),
+            None,
+        )
+    }
+}
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::checks::get_vote_account_node_pubkey;
+use crate::events::management::RegisterValidatorEvent;
+use crate::state::validator_bond::ValidatorBond;
+use crate::state::validator_system::{ValidatorList, ValidatorRecord};
+use crate::{error::MarinadeError, State};
+
+#[derive(Accounts)]
+pub struct RegisterValidator<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+
+    /// CHECK: parsed in code
+    pub validator_vote: UncheckedAccount<'info>,
+    /// the node identity of the vote account, pays the rent and the bond
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub validator_identity: Signer<'info>,
+
+    /// CHECK: no discriminator used
+    /// by initializing this account we mark the validator as added
+    #[account(
+        init, // will ensure it is system account
+        payer = validator_identity,
+        space = 0,
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorRecord::DUPLICATE_FLAG_SEED,
+            &validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub duplication_flag: UncheckedAccount<'info>,
+    /// created here or already posted with init_validator_bond
+    #[account(
+        init_if_needed,
+        payer = validator_identity,
+        space = ValidatorBond::serialized_len(),
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorBond::SEED,
+            &validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub validator_bond: Account<'info, ValidatorBond>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RegisterValidator<'info> {
+    pub fn process(&mut self, bond_lamports: u64, bond_bump: u8) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        require!(
+            self.state.permissionless_add_validator_enabled,
+            MarinadeError::PermissionlessAddValidatorIsNotEnabled
+        );
+        require!(
+            self.state.delinquent_upgrader.is_done(),
+            MarinadeError::DelinquentUpgraderIsNotDone
+        );
+        require_keys_eq!(
+            get_vote_account_node_pubkey(&self.validator_vote)?,
+            self.validator_identity.key(),
+            MarinadeError::WrongValidatorIdentity
+        );
+
+        msg!("Register validator {}", self.validator_vote.key);
+
+        if self.validator_bond.state == Pubkey::default() {
+            self.validator_bond.set_inner(ValidatorBond {
+                state: self.state.key(),
+                validator_vote: self.validator_vote.key(),
+                authority: self.validator_identity.key(),
+                bump: bond_bump,
+                last_settled_epoch: self.clock.epoch,
+                withdraw_requested_epoch: ValidatorBond::NO_WITHDRAW_REQUEST,
+            });
+        } else {
+            // bond posted before with init_validator_bond or left from a previous registration
+            require_keys_eq!(
+                self.validator_bond.authority,
+                self.validator_identity.key(),
+                MarinadeError::WrongValidatorIdentity
+            );
+            self.validator_bond.withdraw_requested_epoch = ValidatorBond::NO_WITHDRAW_REQUEST;
+        }
+        transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.validator_identity.to_account_info(),
+                    to: self.validator_bond.to_account_info(),
+                },
+            ),
+            bond_lamports,
+        )?;
+        require_gte!(
+            ValidatorBond::balance(&self.validator_bond.to_account_info(), &self.rent),
+            self.state.min_validator_bond,
+            MarinadeError::ValidatorBondIsTooLow
+        );
+
+        // added with score 0, the manager decides when to start staking to it
+        let state_address = self.state.key();
+        self.state.validator_system.add(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            self.validator_vote.key(),
+            0,
+            &state_address,
+            self.duplication_flag.key,
+        )?;
+
+        emit!(RegisterValidatorEvent {
+            state: self.state.key(),
+            validator: self.validator_vote.key(),
+            index: self.state.validator_system.validator_count() - 1,
+            identity: self.validator_identity.key(),
+            bond_lamports,
+        });
+
+        Ok(())
+    }
+}
@@ -15,11 +15,12 @@ pub struct RemoveValidator<'info> {
         has_one = operational_sol_account
     )]
     pub state: Account<'info, State>,
+    /// CHECK: must sign unless the validator is marked for removal
     #[account(
         address = state.validator_system.manager_authority
             @ MarinadeError::InvalidValidatorManager
     )]
-    pub manager_authority: Signer<'info>,
+    pub manager_authority: UncheckedAccount<'info>,
     #[account(
         mut,
         address = state.validator_system.validator_list.account,
@@ -57,12 +58,26 @@ impl<'info> RemoveValidator<'info> {
             &validator_vote,
         )?;
 
+        // anyone can finalize the removal of a drained validator marked for removal or migrated
+        if !validator.marked_for_removal && validator.migrated_to == Pubkey::default() {
+            require!(
+                self.manager_authority.is_signer,
+                MarinadeError::InvalidValidatorManager
+            );
+        }
+
         require_keys_eq!(
             self.duplication_flag.key(),
             validator.duplication_flag_address(self.state.to_account_info().key),
             MarinadeError::WrongValidatorDuplicationFlag
         );
 
+        if validator.pending_score_epoch != 0 {
+            // nothing to promote anymore
+            self.state.pending_scores_count -= 1;
+        }
+
+        self.state.total_capped_stake -= validator.capped_stake;
         self.state.validator_system.remove(
             &mut self
                 .validator_list
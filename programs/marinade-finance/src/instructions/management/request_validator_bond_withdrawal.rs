@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarinadeError,
+    events::management::RequestValidatorBondWithdrawalEvent,
+    state::{validator_bond::ValidatorBond, validator_system::ValidatorRecord},
+    State, ID,
+};
+
+#[derive(Accounts)]
+pub struct RequestValidatorBondWithdrawal<'info> {
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        has_one = state,
+        has_one = authority @ MarinadeError::WrongValidatorIdentity,
+    )]
+    pub validator_bond: Account<'info, ValidatorBond>,
+    pub authority: Signer<'info>,
+    /// CHECK: manual account processing
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorRecord::DUPLICATE_FLAG_SEED,
+            &validator_bond.validator_vote.to_bytes(),
+        ],
+        bump,
+    )]
+    pub duplication_flag: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> RequestValidatorBondWithdrawal<'info> {
+    /// starts the WITHDRAW_DELAY_EPOCHS delay, the admin can still slash the bond meanwhile
+    pub fn process(&mut self) -> Result<()> {
+        // the flag is given back to the system program when the validator is removed
+        require_keys_neq!(
+            *self.duplication_flag.owner,
+            ID,
+            MarinadeError::ValidatorIsStillListed
+        );
+        require_eq!(
+            self.validator_bond.withdraw_requested_epoch,
+            ValidatorBond::NO_WITHDRAW_REQUEST,
+            MarinadeError::ValidatorBondWithdrawalAlreadyRequested
+        );
+
+        self.validator_bond.withdraw_requested_epoch = self.clock.epoch;
+
+        emit!(RequestValidatorBondWithdrawalEvent {
+            state: self.state.key(),
+            validator: self.validator_bond.validator_vote,
+            authority: self.authority.key(),
+            epoch: self.clock.epoch,
+            withdrawable_epoch: self.clock.epoch + ValidatorBond::WITHDRAW_DELAY_EPOCHS,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarinadeError, events::management::ScheduleValidatorScoresEvent,
+    state::validator_system::ValidatorList, State,
+};
+
+use super::ValidatorScoreUpdate;
+
+#[derive(Accounts)]
+pub struct ScheduleValidatorScores<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ MarinadeError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> ScheduleValidatorScores<'info> {
+    /// Schedule scores to be effective from the next epoch.
+    /// A rescoring round can be sent in several transactions during the epoch,
+    /// all the scores are promoted together by promote_validator_scores
+    pub fn process(&mut self, updates: Vec<ValidatorScoreUpdate>) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+
+        let effective_epoch = self.clock.epoch + 1;
+        // the previous round must be fully promoted before starting a new one
+        if self.state.pending_scores_count > 0 {
+            require_eq!(
+                self.state.pending_scores_epoch,
+                effective_epoch,
+                MarinadeError::PendingScoresNotPromoted
+            );
+        }
+        self.state.pending_scores_epoch = effective_epoch;
+
+        let validator_list_info = self.validator_list.to_account_info();
+        let mut validator_list_data = validator_list_info.data.borrow_mut();
+        for update in updates.iter() {
+            let mut validator = self.state.validator_system.get_checked(
+                &validator_list_data,
+                update.index,
+                &update.validator_vote,
+            )?;
+            if validator.pending_score_epoch == 0 {
+                self.state.pending_scores_count += 1;
+            }
+            validator.pending_score = update.score;
+            validator.pending_score_epoch = effective_epoch;
+            self.state
+                .validator_system
+                .set(&mut validator_list_data, update.index, validator)?;
+        }
+
+        emit!(ScheduleValidatorScoresEvent {
+            state: self.state.key(),
+            effective_epoch,
+            scheduled_count: updates.len() as u32,
+            pending_scores_count: self.state.pending_scores_count,
+        });
+
+        Ok(())
+    }
+}
@@ -33,13 +33,13 @@ impl<'info> SetValidatorScore<'info> {
             &validator_vote,
         )?;
 
-        self.state.validator_system.total_validator_score -= validator.score;
+        self.state.validator_system.total_validator_score -= validator.effective_score();
         let score_change = {
             let old = validator.score;
             validator.score = score;
             U32ValueChange { old, new: score }
         };
-        self.state.validator_system.total_validator_score += score;
+        self.state.validator_system.total_validator_score += validator.effective_score();
         self.state.validator_system.set(
             &mut self.validator_list.to_account_info().data.borrow_mut(),
             index,
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarinadeError,
+    events::{management::SetValidatorScoresEvent, U32ValueChange},
+    state::validator_system::ValidatorList,
+    State,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct ValidatorScoreUpdate {
+    pub index: u32,
+    pub validator_vote: Pubkey,
+    pub score: u32,
+}
+
+#[derive(Accounts)]
+pub struct SetValidatorScores<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ MarinadeError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+}
+
+impl<'info> SetValidatorScores<'info> {
+    pub fn process(&mut self, updates: Vec<ValidatorScoreUpdate>) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+
+        let validator_list_info = self.validator_list.to_account_info();
+        let mut validator_list_data = validator_list_info.data.borrow_mut();
+        let mut total_validator_score = self.state.validator_system.total_validator_score;
+        for update in updates.iter() {
+            let mut validator = self.state.validator_system.get_checked(
+                &validator_list_data,
+                update.index,
+                &update.validator_vote,
+            )?;
+            total_validator_score -= validator.effective_score();
+            validator.score = update.score;
+            total_validator_score += validator.effective_score();
+            self.state
+                .validator_system
+                .set(&mut validator_list_data, update.index, validator)?;
+        }
+
+        let total_validator_score_change = {
+            let old = self.state.validator_system.total_validator_score;
+            self.state.validator_system.total_validator_score = total_validator_score;
+            U32ValueChange {
+                old,
+                new: total_validator_score,
+            }
+        };
+
+        emit!(SetValidatorScoresEvent {
+            state: self.state.key(),
+            updated_count: updates.len() as u32,
+            total_validator_score_change,
+        });
+
+        Ok(())
+    }
+}
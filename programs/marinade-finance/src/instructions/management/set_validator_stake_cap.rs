@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarinadeError,
+    events::{management::SetValidatorStakeCapEvent, U64ValueChange},
+    state::validator_system::ValidatorList,
+    State,
+};
+
+#[derive(Accounts)]
+pub struct SetValidatorStakeCap<'info> {
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ MarinadeError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+}
+
+impl<'info> SetValidatorStakeCap<'info> {
+    // stake_cap = 0 removes the per-validator cap, the global max_validator_stake_share still applies.
+    // A lower cap applies at once, check_validator_stake_cap redistributes the stake cut off by it
+    pub fn process(&mut self, index: u32, validator_vote: Pubkey, stake_cap: u64) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+
+        let mut validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            &validator_vote,
+        )?;
+
+        let stake_cap_change = {
+            let old = validator.stake_cap;
+            validator.stake_cap = stake_cap;
+            U64ValueChange {
+                old,
+                new: stake_cap,
+            }
+        };
+        self.state.validator_system.set(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            index,
+            validator,
+        )?;
+
+        emit!(SetValidatorStakeCapEvent {
+            state: self.state.key(),
+            validator: validator_vote,
+            index,
+            stake_cap_change,
+        });
+
+        Ok(())
+    }
+}
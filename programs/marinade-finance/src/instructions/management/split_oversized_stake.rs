@@ -0,0 +1,247 @@
+use crate::{
+    checks::check_stake_amount_and_validator,
+    error::MarinadeError,
+    events::management::SplitOversizedStakeEvent,
+    state::stake_system::{StakeList, StakeRecord, StakeStatus, StakeSystem},
+    State,
+};
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    program::invoke_signed, stake, stake::state::StakeState, system_program, sysvar::stake_history,
+};
+use anchor_lang::system_program::{create_account, CreateAccount};
+use anchor_spl::stake::{withdraw, Stake, StakeAccount, Withdraw};
+
+#[derive(Accounts)]
+pub struct SplitOversizedStake<'info> {
+    #[account(mut)]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ MarinadeError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+    #[account(mut)]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_DEPOSIT_SEED
+        ],
+        bump = state.stake_system.stake_deposit_bump_seed
+    )]
+    pub stake_deposit_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = split_stake_rent_payer,
+        space = std::mem::size_of::<StakeState>(),
+        owner = stake::program::ID,
+    )]
+    pub split_stake_account: Account<'info, StakeAccount>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub split_stake_rent_payer: Signer<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: have no CPU budget to parse
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub stake_program: Program<'info, Stake>,
+}
+
+impl<'info> SplitOversizedStake<'info> {
+    /// Splits state.max_stake_account_size lamports off an active stake account bigger than that
+    /// into new active stake accounts of the same validator, so later unstakes and withdrawals
+    /// don't need partial deactivations. The first split goes to split_stake_account,
+    /// the next ones to the extra split accounts (new keypairs signing the transaction)
+    /// while the remainder is still oversized. Only the manager can split as every split
+    /// takes a stake_list record
+    pub fn process(
+        &mut self,
+        stake_index: u32,
+        extra_split_stake_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(!self.state.paused, MarinadeError::ProgramIsPaused);
+        require!(
+            self.state.delinquent_upgrader.is_done(),
+            MarinadeError::DelinquentUpgraderIsNotDone
+        );
+
+        // the index is a hint, the record is looked up by the account key if it was moved
+        let (stake_index, mut stake) = self.state.stake_system.get_by_key(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
+        require_eq!(
+            stake.last_update_status,
+            StakeStatus::Active,
+            MarinadeError::RequiredActiveStake
+        );
+        require!(
+            !stake.is_emergency_unstaking,
+            MarinadeError::StakeAccountIsEmergencyUnstaking
+        );
+        let validator_vote = self
+            .stake_account
+            .delegation()
+            .ok_or(error!(MarinadeError::StakeNotDelegated))?
+            .voter_pubkey;
+        check_stake_amount_and_validator(
+            &self.stake_account,
+            stake.last_update_delegated_lamports,
+            &validator_vote,
+        )?;
+
+        if !self
+            .state
+            .is_oversized_stake(stake.last_update_delegated_lamports)
+        {
+            msg!(
+                "Stake {} of {} lamports is not over max stake account size {}",
+                stake.stake_account,
+                stake.last_update_delegated_lamports,
+                self.state.max_stake_account_size
+            );
+            self.return_unused_split_stake_account_rent()?;
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+
+        let split_stake_account = self.split_stake_account.to_account_info();
+        self.split(
+            stake_index,
+            &mut stake,
+            &split_stake_account,
+            validator_vote,
+        )?;
+        for extra_split_stake_account in extra_split_stake_accounts {
+            if !self
+                .state
+                .is_oversized_stake(stake.last_update_delegated_lamports)
+            {
+                // the unused extra accounts were not created
+                break;
+            }
+            let space = std::mem::size_of::<StakeState>();
+            create_account(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    CreateAccount {
+                        from: self.split_stake_rent_payer.to_account_info(),
+                        to: extra_split_stake_account.clone(),
+                    },
+                ),
+                self.rent.minimum_balance(space),
+                space as u64,
+                &stake::program::ID,
+            )?;
+            self.split(
+                stake_index,
+                &mut stake,
+                extra_split_stake_account,
+                validator_vote,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn split(
+        &mut self,
+        stake_index: u32,
+        stake: &mut StakeRecord,
+        split_stake_account: &AccountInfo<'info>,
+        validator_vote: Pubkey,
+    ) -> Result<()> {
+        let split_amount = self.state.max_stake_account_size;
+        msg!(
+            "Split {} ({} lamports) from stake {}",
+            split_stake_account.key,
+            split_amount,
+            stake.stake_account
+        );
+        let split_instruction = stake::instruction::split(
+            self.stake_account.to_account_info().key,
+            self.stake_deposit_authority.key,
+            split_amount,
+            split_stake_account.key,
+        )
+        .last()
+        .unwrap()
+        .clone();
+        invoke_signed(
+            &split_instruction,
+            &[
+                self.stake_program.to_account_info(),
+                self.stake_account.to_account_info(),
+                split_stake_account.clone(),
+                self.stake_deposit_authority.to_account_info(),
+            ],
+            &[&[
+                &self.state.key().to_bytes(),
+                StakeSystem::STAKE_DEPOSIT_SEED,
+                &[self.state.stake_system.stake_deposit_bump_seed],
+            ]],
+        )?;
+
+        // the validator active balance does not change, it is only spread over more accounts
+        stake.last_update_delegated_lamports -= split_amount;
+        self.state.stake_system.set(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            stake_index,
+            *stake,
+        )?;
+        self.state.stake_system.add(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            split_stake_account.key,
+            split_amount,
+            &self.clock,
+            false, // is_emergency_unstaking? no
+            StakeStatus::Active,
+        )?;
+
+        emit!(SplitOversizedStakeEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            stake_index,
+            stake_account: stake.stake_account,
+            split_stake_index: self.state.stake_system.stake_count() - 1,
+            split_stake_account: *split_stake_account.key,
+            validator_vote,
+            split_amount,
+            remaining_amount: stake.last_update_delegated_lamports,
+        });
+
+        Ok(())
+    }
+
+    pub fn return_unused_split_stake_account_rent(&self) -> Result<()> {
+        // Return back the rent reserve of unused split stake account in case of early return
+        withdraw(
+            CpiContext::new(
+                self.stake_program.to_account_info(),
+                Withdraw {
+                    stake: self.split_stake_account.to_account_info(),
+                    withdrawer: self.split_stake_account.to_account_info(),
+                    to: self.split_stake_rent_payer.to_account_info(),
+                    clock: self.clock.to_account_info(),
+                    stake_history: self.stake_history.to_account_info(),
+                },
+            ),
+            self.split_stake_account.to_account_info().lamports(),
+            None,
+        )
+    }
+}
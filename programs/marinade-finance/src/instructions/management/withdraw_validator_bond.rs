@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarinadeError,
+    events::management::WithdrawValidatorBondEvent,
+    state::{validator_bond::ValidatorBond, validator_system::ValidatorRecord},
+    State, ID,
+};
+
+#[derive(Accounts)]
+pub struct WithdrawValidatorBond<'info> {
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        has_one = state,
+        has_one = authority @ MarinadeError::WrongValidatorIdentity,
+        close = authority,
+    )]
+    pub validator_bond: Account<'info, ValidatorBond>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: manual account processing
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorRecord::DUPLICATE_FLAG_SEED,
+            &validator_bond.validator_vote.to_bytes(),
+        ],
+        bump,
+    )]
+    pub duplication_flag: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> WithdrawValidatorBond<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        // the flag is given back to the system program when the validator is removed
+        require_keys_neq!(
+            *self.duplication_flag.owner,
+            ID,
+            MarinadeError::ValidatorIsStillListed
+        );
+        // request_validator_bond_withdrawal first, so the admin has time to slash the removed validator
+        require!(
+            self.validator_bond.is_withdrawable(self.clock.epoch),
+            MarinadeError::ValidatorBondIsLocked
+        );
+
+        emit!(WithdrawValidatorBondEvent {
+            state: self.state.key(),
+            validator: self.validator_bond.validator_vote,
+            authority: self.authority.key(),
+            lamports: self.validator_bond.to_account_info().lamports(),
+        });
+
+        Ok(())
+    }
+}
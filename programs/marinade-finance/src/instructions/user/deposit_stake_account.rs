@@ -1,15 +1,18 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::stake::instruction::LockupArgs;
 use anchor_lang::solana_program::{
-    program::invoke, stake, stake::state::StakeAuthorize, system_program,
+    program::invoke,
+    stake,
+    stake::state::{Delegation, StakeAuthorize},
+    system_program,
 };
 use anchor_spl::stake::{Stake, StakeAccount};
 use anchor_spl::token::{mint_to, Mint, MintTo, Token, TokenAccount};
 
-use crate::events::user::DepositStakeAccountEvent;
+use crate::events::user::{DepositStakeAccountEvent, DepositUnlistedStakeAccountEvent};
 use crate::state::delinquent_upgrader::DelinquentUpgraderState;
-use crate::state::stake_system::StakeList;
-use crate::state::validator_system::ValidatorList;
+use crate::state::stake_system::{StakeList, StakeStatus};
+use crate::state::validator_system::{ValidatorList, ValidatorRecord};
 use crate::{error::MarinadeError, require_lte, state::stake_system::StakeSystem, State, ID};
 
 #[derive(Accounts)]
@@ -35,6 +38,7 @@ pub struct DepositStakeAccount<'info> {
     pub stake_account: Box<Account<'info, StakeAccount>>,
     pub stake_authority: Signer<'info>,
     /// CHECK: manual account processing, only required if adding validator (if allowed)
+    /// or to prove the validator is not listed when depositing unlisted stake
     #[account(mut)]
     pub duplication_flag: UncheckedAccount<'info>,
     #[account(
@@ -73,6 +77,187 @@ pub struct DepositStakeAccount<'info> {
 impl<'info> DepositStakeAccount<'info> {
     pub const WAIT_EPOCHS: u64 = 0; // Accepting fresh/redelegated accounts also because those are mergeable anyways
     pub fn process(&mut self, validator_index: u32) -> Result<()> {
+        // record values for event log
+        let user_msol_balance = self.mint_to.amount;
+        let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
+        let msol_supply = self.state.msol_supply;
+
+        let delegation = self.check_deposited_stake()?;
+
+        let mut validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &delegation.voter_pubkey,
+        )?;
+        // record balance for event log
+        let validator_active_balance = validator.active_balance;
+        // update validator.active_balance
+        validator.active_balance += delegation.stake;
+        // Maintain delinquent_upgrader invariants
+        match &mut self.state.delinquent_upgrader {
+            DelinquentUpgraderState::IteratingStakes {
+                visited_count,
+                total_active_balance,
+                ..
+            } => {
+                // It will be added as processed already
+                *visited_count += 1;
+                *total_active_balance += delegation.stake;
+                validator.delinquent_upgrader_active_balance += delegation.stake;
+            }
+            DelinquentUpgraderState::IteratingValidators { visited_count, .. } => {
+                if validator_index >= *visited_count {
+                    // Maintain delinquent_upgrader_active_balance property
+                    // because it will be used in the further finalize_delinquent_upgrade call
+                    validator.delinquent_upgrader_active_balance += delegation.stake;
+                }
+                // otherwise the field is already zeroed
+            }
+            DelinquentUpgraderState::Done => {
+                // Invariants are not needed anymore and fields are already zeroed
+            }
+        }
+
+        self.state.validator_system.set(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+
+        let old_withdrawer = self.take_stake_authority()?;
+
+        self.state.stake_system.add(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            self.stake_account.to_account_info().key,
+            delegation.stake,
+            &self.clock,
+            false, // is_emergency_unstaking? no
+            StakeStatus::Active,
+        )?;
+
+        let sol_fees = self.state.deposit_stake_account_fee.apply(delegation.stake);
+        let msol_to_mint = self.mint_msol(delegation.stake.saturating_sub(sol_fees))?;
+
+        // record current total_active_balance for the event log
+        let total_active_balance = self.state.validator_system.total_active_balance;
+        // update total_active_balance
+        self.state.validator_system.total_active_balance += delegation.stake;
+
+        emit!(DepositStakeAccountEvent {
+            state: self.state.key(),
+            stake: self.stake_account.key(),
+            delegated: delegation.stake,
+            withdrawer: old_withdrawer,
+            stake_index: self.state.stake_system.stake_count() - 1,
+            validator: delegation.voter_pubkey,
+            validator_index,
+            validator_active_balance,
+            total_active_balance,
+            user_msol_balance,
+            msol_minted: msol_to_mint,
+            total_virtual_staked_lamports,
+            msol_supply,
+            sol_fees,
+        });
+        Ok(())
+    }
+
+    /// Deposit of a stake account delegated to a validator that is not in the validator list.
+    /// The stake is deactivated right away and accounted in emergency_cooling_down,
+    /// once update_deactivated moves the lamports to the reserve they are restaked by stake-delta
+    pub fn process_unlisted(&mut self) -> Result<()> {
+        require!(
+            self.state.unlisted_stake_deposit_enabled,
+            MarinadeError::UnlistedStakeDepositIsNotEnabled
+        );
+        // the validator list is not touched so keep it simple and do not maintain upgrader invariants
+        require!(
+            self.state.delinquent_upgrader.is_done(),
+            MarinadeError::DelinquentUpgraderIsNotDone
+        );
+
+        // record values for event log
+        let user_msol_balance = self.mint_to.amount;
+        let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
+        let msol_supply = self.state.msol_supply;
+
+        let delegation = self.check_deposited_stake()?;
+
+        // the validator is listed if its duplication flag is initialized
+        let (duplication_flag, _) =
+            ValidatorRecord::find_duplication_flag(&self.state.key(), &delegation.voter_pubkey);
+        require_keys_eq!(
+            self.duplication_flag.key(),
+            duplication_flag,
+            MarinadeError::WrongValidatorDuplicationFlag
+        );
+        require_keys_neq!(
+            *self.duplication_flag.owner,
+            ID,
+            MarinadeError::DepositingListedValidatorStake
+        );
+
+        // deactivate with the user stake authority before it is replaced by ours
+        msg!("Deactivate unlisted stake {}", self.stake_account.key());
+        invoke(
+            &stake::instruction::deactivate_stake(
+                self.stake_account.to_account_info().key,
+                self.stake_authority.key,
+            ),
+            &[
+                self.stake_program.to_account_info(),
+                self.stake_account.to_account_info(),
+                self.clock.to_account_info(),
+                self.stake_authority.to_account_info(),
+            ],
+        )?;
+
+        let old_withdrawer = self.take_stake_authority()?;
+
+        self.state.stake_system.add(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            self.stake_account.to_account_info().key,
+            delegation.stake,
+            &self.clock,
+            true, // is_emergency_unstaking? yes, the SOL will be re-staked ASAP
+            StakeStatus::Deactivating,
+        )?;
+
+        let sol_fees = self
+            .state
+            .unlisted_stake_deposit_fee
+            .apply(delegation.stake);
+        let msol_to_mint = self.mint_msol(delegation.stake.saturating_sub(sol_fees))?;
+
+        // record current emergency_cooling_down for the event log
+        let emergency_cooling_down = self.state.emergency_cooling_down;
+        // the lamports are cooling down until update_deactivated
+        self.state.emergency_cooling_down += delegation.stake;
+
+        emit!(DepositUnlistedStakeAccountEvent {
+            state: self.state.key(),
+            stake: self.stake_account.key(),
+            delegated: delegation.stake,
+            withdrawer: old_withdrawer,
+            stake_index: self.state.stake_system.stake_count() - 1,
+            validator: delegation.voter_pubkey,
+            emergency_cooling_down,
+            user_msol_balance,
+            msol_minted: msol_to_mint,
+            total_virtual_staked_lamports,
+            msol_supply,
+            sol_fees,
+        });
+        Ok(())
+    }
+
+    // checks the deposited stake account is active and can be taken over, returns its delegation
+    fn check_deposited_stake(&self) -> Result<Delegation> {
         require!(!self.state.paused, MarinadeError::ProgramIsPaused);
 
         // impossible to happen check outside bug (msol mint auth is a PDA)
@@ -82,11 +267,6 @@ impl<'info> DepositStakeAccount<'info> {
             MarinadeError::UnregisteredMsolMinted
         );
 
-        // record values for event log
-        let user_msol_balance = self.mint_to.amount;
-        let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
-        let msol_supply = self.state.msol_supply;
-
         let delegation = self.stake_account.delegation().ok_or_else(|| {
             error!(MarinadeError::RequiredDelegatedStake).with_account_name("stake_account")
         })?;
@@ -123,59 +303,23 @@ impl<'info> DepositStakeAccount<'info> {
 
         self.state.check_staking_cap(delegation.stake)?;
 
-        let lockup = self.stake_account.lockup().unwrap();
         // Check Lockup
-        if lockup.is_in_force(&self.clock, None) {
+        if self
+            .stake_account
+            .lockup()
+            .unwrap()
+            .is_in_force(&self.clock, None)
+        {
             msg!("Can not deposit stake account with lockup");
             return err!(MarinadeError::StakeAccountWithLockup)
                 .map_err(|e| e.with_account_name("stake_account"));
         }
 
-        let mut validator = self.state.validator_system.get_checked(
-            &self.validator_list.to_account_info().data.as_ref().borrow(),
-            validator_index,
-            &delegation.voter_pubkey,
-        )?;
-        // record balance for event log
-        let validator_active_balance = validator.active_balance;
-        // update validator.active_balance
-        validator.active_balance += delegation.stake;
-        // Maintain delinquent_upgrader invariants
-        match &mut self.state.delinquent_upgrader {
-            DelinquentUpgraderState::IteratingStakes {
-                visited_count,
-                total_active_balance,
-                ..
-            } => {
-                // It will be added as processed already
-                *visited_count += 1;
-                *total_active_balance += delegation.stake;
-                validator.delinquent_upgrader_active_balance += delegation.stake;
-            }
-            DelinquentUpgraderState::IteratingValidators { visited_count, .. } => {
-                if validator_index >= *visited_count {
-                    // Maintain delinquent_upgrader_active_balance property
-                    // because it will be used in the further finalize_delinquent_upgrade call
-                    validator.delinquent_upgrader_active_balance += delegation.stake;
-                }
-                // otherwise the field is already zeroed
-            }
-            DelinquentUpgraderState::Done => {
-                // Invariants are not needed anymore and fields are already zeroed
-            }
-        }
-
-        self.state.validator_system.set(
-            &mut self
-                .validator_list
-                .to_account_info()
-                .data
-                .as_ref()
-                .borrow_mut(),
-            validator_index,
-            validator,
-        )?;
+        Ok(delegation)
+    }
 
+    // sets marinade PDAs as staker and withdrawer of the deposited stake account, returns the old withdrawer
+    fn take_stake_authority(&self) -> Result<Pubkey> {
         {
             let new_staker = Pubkey::create_program_address(
                 &[
@@ -195,7 +339,7 @@ impl<'info> DepositStakeAccount<'info> {
             );
 
             // Clean old lockup
-            if lockup.custodian != Pubkey::default() {
+            if self.stake_account.lockup().unwrap().custodian != Pubkey::default() {
                 invoke(
                     &stake::instruction::set_lockup(
                         &self.stake_account.key(),
@@ -265,18 +409,11 @@ impl<'info> DepositStakeAccount<'info> {
                 ],
             )?;
         }
+        Ok(old_withdrawer)
+    }
 
-        self.state.stake_system.add(
-            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
-            self.stake_account.to_account_info().key,
-            delegation.stake,
-            &self.clock,
-            false, // is_emergency_unstaking? no
-            true,  // is_active? yes
-        )?;
-
-        let sol_fees = self.state.deposit_stake_account_fee.apply(delegation.stake);
-        let deposit_stake_minus_fee = delegation.stake.saturating_sub(sol_fees);
+    // mints mSOL for the deposited lamports (fees already deducted), returns minted amount
+    fn mint_msol(&mut self, deposit_stake_minus_fee: u64) -> Result<u64> {
         let msol_to_mint = self
             .state
             .calc_msol_from_lamports(deposit_stake_minus_fee)?;
@@ -298,28 +435,6 @@ impl<'info> DepositStakeAccount<'info> {
             msol_to_mint,
         )?;
         self.state.on_msol_mint(msol_to_mint);
-
-        // record current total_active_balance for the event log
-        let total_active_balance = self.state.validator_system.total_active_balance;
-        // update total_active_balance
-        self.state.validator_system.total_active_balance += delegation.stake;
-
-        emit!(DepositStakeAccountEvent {
-            state: self.state.key(),
-            stake: self.stake_account.key(),
-            delegated: delegation.stake,
-            withdrawer: old_withdrawer,
-            stake_index: self.state.stake_system.stake_count() - 1,
-            validator: delegation.voter_pubkey,
-            validator_index,
-            validator_active_balance,
-            total_active_balance,
-            user_msol_balance,
-            msol_minted: msol_to_mint,
-            total_virtual_staked_lamports,
-            msol_supply,
-            sol_fees,
-        });
-        Ok(())
+        Ok(msol_to_mint)
     }
 }
@@ -131,7 +131,8 @@ impl<'info> WithdrawStakeAccount<'info> {
         )
         .map_err(|e| e.with_account_name("burn_msol_from"))?;
 
-        let mut stake = self.state.stake_system.get_checked(
+        // the indexes are hints, the records are looked up by the account keys if they were moved
+        let (stake_index, mut stake) = self.state.stake_system.get_by_key(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             stake_index,
             self.stake_account.to_account_info().key,
@@ -159,9 +160,10 @@ impl<'info> WithdrawStakeAccount<'info> {
             MarinadeError::RequiredActiveStake
         );
 
-        let mut validator = self.state.validator_system.get(
+        let (validator_index, mut validator) = self.state.validator_system.get_by_key(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
+            &delegation.voter_pubkey,
         )?;
 
         // check currently_staked in this account & validator vote-key
@@ -120,6 +120,11 @@ pub mod marinade_finance {
         ctx.accounts.process(validator_index)
     }
 
+    pub fn deposit_unlisted_stake_account(ctx: Context<DepositStakeAccount>) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process_unlisted()
+    }
+
     pub fn liquid_unstake(ctx: Context<LiquidUnstake>, msol_amount: u64) -> Result<()> {
         check_context(&ctx)?;
         ctx.accounts.process(msol_amount)
@@ -171,6 +176,17 @@ pub mod marinade_finance {
         ctx.accounts.process(validator_index)
     }
 
+    pub fn stake_reserve_most_under_target(
+        ctx: Context<StakeReserve>,
+        validator_index: u32,
+        page_start: u32,
+        page_len: u32,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts
+            .process_most_under_target(validator_index, page_start, page_len)
+    }
+
     pub fn update_active(
         ctx: Context<UpdateActive>,
         stake_index: u32,
@@ -179,6 +195,16 @@ pub mod marinade_finance {
         check_context(&ctx)?;
         ctx.accounts.process(stake_index, validator_index)
     }
+    pub fn update_active_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateActive<'info>>,
+        items: Vec<UpdateActiveBatchItem>,
+    ) -> Result<()> {
+        // remaining accounts are the stake accounts of the batch, so check_context can't be used
+        if !check_id(ctx.program_id) {
+            return err!(MarinadeError::InvalidProgramId);
+        }
+        ctx.accounts.process_batch(items, ctx.remaining_accounts)
+    }
     pub fn update_deactivated(
         ctx: Context<UpdateDeactivated>,
         stake_index: u32,
@@ -206,6 +232,19 @@ pub mod marinade_finance {
         ctx.accounts.process(stake_index, validator_index)
     }
 
+    pub fn emergency_unstake_validator<'info>(
+        ctx: Context<'_, '_, '_, 'info, EmergencyUnstakeValidator<'info>>,
+        validator_index: u32,
+        stake_indexes: Vec<u32>,
+    ) -> Result<()> {
+        // remaining accounts are the stake accounts of the validator, so check_context can't be used
+        if !check_id(ctx.program_id) {
+            return err!(MarinadeError::InvalidProgramId);
+        }
+        ctx.accounts
+            .process(validator_index, stake_indexes, ctx.remaining_accounts)
+    }
+
     pub fn partial_unstake(
         ctx: Context<PartialUnstake>,
         stake_index: u32,
@@ -267,6 +306,14 @@ pub mod marinade_finance {
         ctx.accounts.process(capacity)
     }
 
+    pub fn resize_validator_records(
+        ctx: Context<ResizeValidatorRecords>,
+        record_size: u32,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(record_size)
+    }
+
     pub fn realloc_stake_list(ctx: Context<ReallocStakeList>, capacity: u32) -> Result<()> {
         check_context(&ctx)?;
         ctx.accounts.process(capacity)
@@ -279,4 +326,172 @@ pub mod marinade_finance {
         check_context(&ctx)?;
         ctx.accounts.process(max_validators)
     }
+
+    pub fn register_validator(ctx: Context<RegisterValidator>, bond_lamports: u64) -> Result<()> {
+        check_context(&ctx)?;
+        let bond_bump = *ctx.bumps.get("validator_bond").unwrap();
+        ctx.accounts.process(bond_lamports, bond_bump)
+    }
+
+    pub fn request_validator_bond_withdrawal(
+        ctx: Context<RequestValidatorBondWithdrawal>,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
+    pub fn withdraw_validator_bond(ctx: Context<WithdrawValidatorBond>) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
+    pub fn slash_validator_bond(ctx: Context<SlashValidatorBond>, lamports: u64) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(lamports)
+    }
+
+    pub fn init_validator_bond(ctx: Context<InitValidatorBond>, bond_lamports: u64) -> Result<()> {
+        check_context(&ctx)?;
+        let bond_bump = *ctx.bumps.get("validator_bond").unwrap();
+        ctx.accounts.process(bond_lamports, bond_bump)
+    }
+
+    pub fn settle_validator_bond(
+        ctx: Context<SettleValidatorBond>,
+        validator_index: u32,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(validator_index)
+    }
+
+    pub fn set_validator_stake_cap(
+        ctx: Context<SetValidatorStakeCap>,
+        index: u32,
+        validator_vote: Pubkey,
+        stake_cap: u64,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(index, validator_vote, stake_cap)
+    }
+
+    pub fn check_validator_commission(
+        ctx: Context<CheckValidatorCommission>,
+        validator_index: u32,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(validator_index)
+    }
+
+    pub fn check_validator_stake_cap(
+        ctx: Context<CheckValidatorStakeCap>,
+        validator_index: u32,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(validator_index)
+    }
+
+    pub fn check_validator_delinquency(
+        ctx: Context<CheckValidatorDelinquency>,
+        validator_index: u32,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(validator_index)
+    }
+
+    pub fn set_validator_scores(
+        ctx: Context<SetValidatorScores>,
+        updates: Vec<ValidatorScoreUpdate>,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(updates)
+    }
+
+    pub fn schedule_validator_scores(
+        ctx: Context<ScheduleValidatorScores>,
+        updates: Vec<ValidatorScoreUpdate>,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(updates)
+    }
+
+    pub fn promote_validator_scores(
+        ctx: Context<PromoteValidatorScores>,
+        start_index: u32,
+        max_validators: u32,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(start_index, max_validators)
+    }
+
+    pub fn redelegate(
+        ctx: Context<Redelegate>,
+        stake_index: u32,
+        source_validator_index: u32,
+        dest_validator_index: u32,
+        desired_redelegate_amount: u64,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(
+            stake_index,
+            source_validator_index,
+            dest_validator_index,
+            desired_redelegate_amount,
+        )
+    }
+
+    pub fn mark_validator_for_removal(
+        ctx: Context<MarkValidatorForRemoval>,
+        index: u32,
+        validator_vote: Pubkey,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(index, validator_vote)
+    }
+
+    pub fn migrate_validator(ctx: Context<MigrateValidator>, index: u32) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(index)
+    }
+
+    pub fn reconcile_deactivated_stake(
+        ctx: Context<ReconcileDeactivatedStake>,
+        stake_index: u32,
+        validator_index: u32,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts.process(stake_index, validator_index)
+    }
+
+    pub fn merge_stake_accounts(
+        ctx: Context<MergeStakeAccounts>,
+        destination_stake_index: u32,
+        source_stake_index: u32,
+        validator_index: u32,
+    ) -> Result<()> {
+        check_context(&ctx)?;
+        ctx.accounts
+            .process(destination_stake_index, source_stake_index, validator_index)
+    }
+
+    pub fn split_oversized_stake<'info>(
+        ctx: Context<'_, '_, '_, 'info, SplitOversizedStake<'info>>,
+        stake_index: u32,
+    ) -> Result<()> {
+        // remaining accounts are the extra split stake accounts, so check_context can't be used
+        if !check_id(ctx.program_id) {
+            return err!(MarinadeError::InvalidProgramId);
+        }
+        ctx.accounts.process(stake_index, ctx.remaining_accounts)
+    }
+
+    pub fn gc_stake_list<'info>(
+        ctx: Context<'_, '_, '_, 'info, GcStakeList<'info>>,
+        start_index: u32,
+    ) -> Result<()> {
+        // remaining accounts are the stake accounts of the scanned records, so check_context can't be used
+        if !check_id(ctx.program_id) {
+            return err!(MarinadeError::InvalidProgramId);
+        }
+        ctx.accounts.process(start_index, ctx.remaining_accounts)
+    }
 }
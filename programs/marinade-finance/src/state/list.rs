@@ -100,6 +100,30 @@ impl List {
         Ok(())
     }
 
+    /// Max number of items scanned by find_key.
+    /// Each step compares 32 bytes (~30 CU), so a full scan stays under ~60K CU.
+    /// Items past it are not found and the caller must pass the actual index
+    //
+    // The lookup by key is a hint plus this scan instead of a PDA per record storing its index.
+    // A PDA would need the removed and the moved record PDAs in every instruction removing
+    // a record (and a new one in every instruction adding one), the moved one is only known
+    // at execution so the race on indexes moves to those instructions.
+    // Limits of the scan:
+    // - a record at an index >= MAX_FIND_KEY_SCAN is found only by its exact index,
+    //   so after a remove moves it (it was the last record and fills the hole of the removed one)
+    //   the instructions using it fail with Wrong*AccountOrIndex until the caller reads the new index
+    // - a wrong hint costs up to MAX_FIND_KEY_SCAN steps of the compute budget
+    pub const MAX_FIND_KEY_SCAN: u32 = 2_000;
+
+    /// find the index of the item starting with the key (list items are records keyed by their first pubkey).
+    /// It's a linear scan, only the first MAX_FIND_KEY_SCAN items are checked
+    pub fn find_key(&self, data: &[u8], key: &Pubkey) -> Option<u32> {
+        (0..self.len().min(Self::MAX_FIND_KEY_SCAN)).find(|index| {
+            let start = 8 + (index * self.item_size()) as usize;
+            data[start..start + 32] == key.to_bytes()
+        })
+    }
+
     pub fn push<I: AnchorSerialize>(&mut self, data: &mut [u8], item: I) -> Result<()> {
         let capacity = self.capacity(data.len())?;
         require_lt!(self.len(), capacity, MarinadeError::ListOverflow);
@@ -169,4 +193,90 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_remove_backwards() -> Result<()> {
+        // removing while scanning a page backwards, like gc_stake_list does
+        const COUNT: u32 = 10;
+        for (page_start, page_end) in [(4, COUNT), (2, 7)] {
+            let mut list_data = [0; COUNT as usize + 8];
+            let mut list = List::new(
+                &[1, 2, 3, 4, 5, 6, 7, 8],
+                1u32,
+                Pubkey::new_unique(),
+                &mut list_data,
+            )?;
+            for i in 0..COUNT {
+                list.push::<u8>(&mut list_data, i as u8)?;
+            }
+            for index in (page_start..page_end).rev() {
+                // the records still to scan keep their index
+                let item = list.get::<u8>(&list_data, index)?;
+                assert_eq!(item, index as u8);
+                if item % 2 == 0 {
+                    list.remove(&mut list_data, index)?;
+                }
+            }
+            let expected_set: BTreeSet<u8> = (0..COUNT as u8)
+                .filter(|i| !(page_start as u8..page_end as u8).contains(i) || i % 2 == 1)
+                .collect();
+            let result_set = (0..list.len())
+                .map(|i| list.get::<u8>(&list_data, i))
+                .collect::<Result<BTreeSet<u8>>>()?;
+            assert_eq!(expected_set, result_set);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_key() -> Result<()> {
+        const COUNT: usize = 10;
+        const ITEM_SIZE: usize = 40;
+        let mut list_data = [0; COUNT * ITEM_SIZE + 8];
+        let list_account = Pubkey::new_unique();
+        let discriminator = &[1, 2, 3, 4, 5, 6, 7, 8];
+        let mut list = List::new(
+            discriminator,
+            ITEM_SIZE as u32,
+            list_account,
+            &mut list_data,
+        )?;
+        let keys: Vec<Pubkey> = (0..COUNT).map(|_| Pubkey::new_unique()).collect();
+        for (i, key) in keys.iter().enumerate() {
+            list.push::<(Pubkey, u64)>(&mut list_data, (*key, i as u64))?;
+        }
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(list.find_key(&list_data, key), Some(i as u32));
+        }
+        assert_eq!(list.find_key(&list_data, &Pubkey::new_unique()), None);
+
+        // the last item is moved into the hole
+        list.remove(&mut list_data, 2)?;
+        assert_eq!(list.find_key(&list_data, &keys[2]), None);
+        assert_eq!(list.find_key(&list_data, &keys[COUNT - 1]), Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_key_scan_is_bounded() -> Result<()> {
+        const COUNT: usize = List::MAX_FIND_KEY_SCAN as usize + 1;
+        const ITEM_SIZE: usize = 32;
+        let mut list_data = vec![0; COUNT * ITEM_SIZE + 8];
+        let mut list = List::new(
+            &[1, 2, 3, 4, 5, 6, 7, 8],
+            ITEM_SIZE as u32,
+            Pubkey::new_unique(),
+            &mut list_data,
+        )?;
+        let keys: Vec<Pubkey> = (0..COUNT).map(|_| Pubkey::new_unique()).collect();
+        for key in keys.iter() {
+            list.push(&mut list_data, *key)?;
+        }
+        assert_eq!(
+            list.find_key(&list_data, &keys[COUNT - 2]),
+            Some(COUNT as u32 - 2)
+        );
+        assert_eq!(list.find_key(&list_data, &keys[COUNT - 1]), None);
+        Ok(())
+    }
 }
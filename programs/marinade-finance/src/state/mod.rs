@@ -1,5 +1,6 @@
 use crate::{
-    calc::{shares_from_value, value_from_shares},
+    calc::{proportional, shares_from_value, value_from_shares},
+    checks::get_vote_account_last_vote_slot,
     error::MarinadeError,
     require_lte, ID,
 };
@@ -10,8 +11,10 @@ use anchor_spl::token::spl_token;
 use std::mem::MaybeUninit;
 
 use self::{
-    delinquent_upgrader::DelinquentUpgraderState, liq_pool::LiqPool, stake_system::StakeSystem,
-    validator_system::ValidatorSystem,
+    delinquent_upgrader::DelinquentUpgraderState,
+    liq_pool::LiqPool,
+    stake_system::StakeSystem,
+    validator_system::{ValidatorRecord, ValidatorSystem},
 };
 
 pub mod delayed_unstake_ticket;
@@ -20,6 +23,7 @@ pub mod fee;
 pub mod liq_pool;
 pub mod list;
 pub mod stake_system;
+pub mod validator_bond;
 pub mod validator_system;
 
 pub use fee::Fee;
@@ -99,6 +103,67 @@ pub struct State {
 
     pub deposit_sol_fee: FeeCents,
     pub deposit_stake_account_fee: FeeCents,
+
+    // deposit of stake accounts delegated to validators not in the validator list
+    // such stakes are deactivated on deposit and counted in emergency_cooling_down
+    // until update_deactivated moves the lamports to the reserve for restaking
+    pub unlisted_stake_deposit_enabled: bool,
+    // can't be lower than deposit_stake_account_fee while enabled (checked by config_marinade),
+    // it covers the epoch of rewards lost while cooling down
+    pub unlisted_stake_deposit_fee: FeeCents,
+
+    // validators can add themselves to the list by posting a bond
+    // of at least min_validator_bond lamports in their ValidatorBond PDA
+    pub permissionless_add_validator_enabled: bool,
+    pub min_validator_bond: u64,
+
+    // rewards a validator is expected to produce per epoch for each lamport staked,
+    // the shortfall is covered from its ValidatorBond by settle_validator_bond
+    pub expected_epoch_reward_rate: FeeCents,
+
+    // no validator can get a stake target above this % of total_lamports_under_control, 0 = disabled
+    pub max_validator_stake_share: Fee,
+
+    // validators with a higher vote account commission (%) do not receive new stake, 0 = disabled
+    pub max_validator_commission: u8,
+
+    // validators without votes for more slots than this are marked as delinquent, 0 = disabled
+    pub max_delinquent_slots: u64,
+
+    // number of validators with a score scheduled to be effective from pending_scores_epoch
+    // stake-delta is blocked while there are pending scores due so it always runs on a coherent score set
+    pub pending_scores_count: u32,
+    pub pending_scores_epoch: u64,
+
+    // band around a validator stake target. Validators are staked only under target * (1 - band)
+    // and unstaked to rebalance only over target * (1 + band) to avoid churn when the scores wobble.
+    // The stake needed for the tickets is unstaked down to the target (see validator_unstake_bound)
+    pub stake_target_hysteresis: Fee,
+
+    // deactivate_stake rejects unstaking from a validator while a validator
+    // with a strictly lower score is still over its target. The whole validator list is scanned
+    pub unstake_lowest_score_first: bool,
+
+    // active stake accounts bigger than this can be split by split_oversized_stake, 0 = disabled
+    pub max_stake_account_size: u64,
+
+    // when set, the stake-delta window is the last seconds_for_stake_delta seconds of the epoch
+    // estimated from the observed slot rate instead of the last stake_system.slots_for_stake_delta slots
+    pub seconds_for_stake_delta: u64,
+
+    // stake_reserve can run before the stake-delta window when the stake delta is at least this, 0 = disabled
+    pub early_stake_delta_threshold: u64,
+
+    // sum of the capped_stake of the validators capped by check_validator_stake_cap.
+    // The rest of the total stake target is distributed to the uncapped validators by score
+    pub total_capped_stake: u64,
+
+    // the stake program redelegate instruction used by redelegate is not enabled on every cluster
+    pub native_redelegate_enabled: bool,
+
+    // first epoch the stake program warms up and cools down stake at 9% instead of 25%
+    // (reduce_stake_warmup_cooldown feature). 0 = since always, as on the current clusters
+    pub new_warmup_cooldown_rate_epoch: u64,
 }
 
 impl State {
@@ -123,10 +188,14 @@ impl State {
 
     pub const MAX_DEPOSIT_SOL_FEE: FeeCents = FeeCents::from_bp_cents(2000); // 0.2% max fee
     pub const MAX_DEPOSIT_STAKE_ACCOUNT_FEE: FeeCents = FeeCents::from_bp_cents(2000); // 0.2% max fee
+    pub const MAX_UNLISTED_STAKE_DEPOSIT_FEE: FeeCents = FeeCents::from_bp_cents(10_000); // 1% max fee
+    pub const MAX_EXPECTED_EPOCH_REWARD_RATE: FeeCents = FeeCents::from_bp_cents(10_000); // 1% per epoch
 
     // min_stake minimum value is MIN_STAKE_MULTIPLIER * rent_exempt_for_token_acc
     pub const MIN_STAKE_LOWER_LIMIT: u64 = LAMPORTS_PER_SOL / 100;
 
+    pub const MIN_SECONDS_FOR_STAKE_DELTA: u64 = 1_200; // 20 minutes
+
     pub fn serialized_len() -> usize {
         unsafe { MaybeUninit::<Self>::zeroed().assume_init() }
             .try_to_vec()
@@ -289,12 +358,195 @@ impl State {
         self.msol_supply -= amount
     }
 
-    pub fn on_stake_moved(&mut self, amount: u64, clock: &Clock) -> Result<()> {
-        if clock.epoch != self.last_stake_move_epoch {
-            self.last_stake_move_epoch = clock.epoch;
-            self.stake_moved = 0;
+    /// max stake a validator can have, the global share of TVL or its own stake_cap if lower.
+    /// u64::MAX when neither is set
+    pub fn validator_stake_cap(&self, validator: &ValidatorRecord) -> u64 {
+        let global_cap = if self.max_validator_stake_share.basis_points == 0 {
+            std::u64::MAX
+        } else {
+            self.max_validator_stake_share
+                .apply(self.total_lamports_under_control())
+        };
+        if validator.stake_cap > 0 {
+            global_cap.min(validator.stake_cap)
+        } else {
+            global_cap
         }
-        self.stake_moved += amount;
+    }
+
+    /// the validator did not vote for more than max_delinquent_slots (never when disabled)
+    pub fn is_delinquent(&self, last_vote_slot: Option<u64>, clock: &Clock) -> bool {
+        self.max_delinquent_slots > 0
+            && clock.slot.saturating_sub(last_vote_slot.unwrap_or(0)) > self.max_delinquent_slots
+    }
+
+    /// is_delinquent read from the vote account. A closed vote account or one that
+    /// can't be parsed anymore is delinquent too, it will never vote again
+    pub fn is_vote_account_delinquent(&self, validator_vote: &AccountInfo, clock: &Clock) -> bool {
+        match get_vote_account_last_vote_slot(validator_vote) {
+            Ok(last_vote_slot) => self.is_delinquent(last_vote_slot, clock),
+            Err(_) => {
+                msg!("Vote account {} is closed or invalid", validator_vote.key);
+                self.max_delinquent_slots > 0
+            }
+        }
+    }
+
+    pub fn is_commission_exceeded(&self, commission: u8) -> bool {
+        self.max_validator_commission > 0 && commission > self.max_validator_commission
+    }
+
+    /// capped validators get their capped_stake, the others a score share of what is left.
+    /// Always limited by the validator stake cap, it may have changed since the last check_validator_stake_cap
+    pub fn validator_stake_target(
+        &self,
+        validator: &ValidatorRecord,
+        total_stake_target: u64,
+    ) -> Result<u64> {
+        let stake_target = if !validator.is_eligible() {
+            0
+        } else if validator.capped_stake > 0 {
+            validator.capped_stake
+        } else {
+            self.validator_system.validator_stake_target(
+                validator,
+                total_stake_target.saturating_sub(self.total_capped_stake),
+            )?
+        };
+        Ok(stake_target.min(self.validator_stake_cap(validator)))
+    }
+
+    /// caps the validator when its score share of the stake not taken by the capped validators
+    /// is over its stake cap, uncaps it otherwise. Keeps the totals in sync
+    pub fn update_validator_capped_stake(
+        &mut self,
+        validator: &mut ValidatorRecord,
+        total_stake_target: u64,
+    ) -> Result<()> {
+        // take the validator out of the totals to get its uncapped share
+        self.validator_system.total_validator_score -= validator.effective_score();
+        self.total_capped_stake -= validator.capped_stake;
+        validator.capped_stake = 0;
+        let score = validator.effective_score();
+        let total_validator_score = self.validator_system.total_validator_score + score;
+        let uncapped_stake_target = if total_validator_score == 0 {
+            0
+        } else {
+            proportional(
+                total_stake_target.saturating_sub(self.total_capped_stake),
+                score as u64,
+                total_validator_score as u64,
+            )?
+        };
+        let stake_cap = self.validator_stake_cap(validator);
+        if uncapped_stake_target > stake_cap {
+            validator.capped_stake = stake_cap;
+        }
+        self.total_capped_stake += validator.capped_stake;
+        self.validator_system.total_validator_score += validator.effective_score();
+        Ok(())
+    }
+
+    /// applies a change of the validator eligibility (commission, delinquency, removal...)
+    /// keeping the totals in sync. A validator no longer eligible releases its capped stake
+    pub fn update_validator_eligibility(
+        &mut self,
+        validator: &mut ValidatorRecord,
+        update: impl FnOnce(&mut ValidatorRecord),
+    ) {
+        self.validator_system.total_validator_score -= validator.effective_score();
+        update(validator);
+        if !validator.is_eligible() {
+            self.total_capped_stake -= validator.capped_stake;
+            validator.capped_stake = 0;
+        }
+        self.validator_system.total_validator_score += validator.effective_score();
+    }
+
+    /// validators with less stake than this are under target and can be staked
+    pub fn validator_stake_lower_bound(&self, validator_stake_target: u64) -> u64 {
+        validator_stake_target - self.stake_target_hysteresis.apply(validator_stake_target)
+    }
+
+    /// validators with more stake than this are over target and can be unstaked
+    pub fn validator_stake_upper_bound(&self, validator_stake_target: u64) -> u64 {
+        validator_stake_target
+            .saturating_add(self.stake_target_hysteresis.apply(validator_stake_target))
+    }
+
+    /// validators with more stake than this can be unstaked.
+    /// When the reserve is short of the tickets (negative stake_delta) the stake over the target
+    /// must be unstaked, the band only keeps the validators in it from being unstaked to rebalance
+    pub fn validator_unstake_bound(&self, validator_stake_target: u64, stake_delta: i128) -> u64 {
+        if stake_delta < 0 {
+            validator_stake_target
+        } else {
+            self.validator_stake_upper_bound(validator_stake_target)
+        }
+    }
+
+    /// a stake over max_stake_account_size can be split by split_oversized_stake
+    /// when the remainder still has min_stake. 0 = splitting is disabled
+    pub fn is_oversized_stake(&self, delegated_lamports: u64) -> bool {
+        self.max_stake_account_size != 0
+            && delegated_lamports >= self.max_stake_account_size + self.stake_system.min_stake
+    }
+
+    /// stake-delta runs only at the end of the epoch: in the last stake_system.slots_for_stake_delta slots
+    /// or, when seconds_for_stake_delta is set, when the epoch end estimated from the slot rate
+    /// observed since the epoch start is closer than seconds_for_stake_delta
+    pub fn is_in_stake_delta_window(
+        &self,
+        clock: &Clock,
+        epoch_schedule: &EpochSchedule,
+    ) -> Result<bool> {
+        let last_slot = epoch_schedule.get_last_slot_in_epoch(clock.epoch);
+        if self.seconds_for_stake_delta == 0 {
+            return Ok(
+                clock.slot >= last_slot.saturating_sub(self.stake_system.slots_for_stake_delta)
+            );
+        }
+        let elapsed_slots = clock.slot - epoch_schedule.get_first_slot_in_epoch(clock.epoch);
+        let elapsed_seconds =
+            u64::try_from(clock.unix_timestamp - clock.epoch_start_timestamp).unwrap_or(0);
+        if elapsed_slots == 0 || elapsed_seconds == 0 {
+            // no slot rate to estimate from, the epoch just started
+            return Ok(false);
+        }
+        let estimated_seconds_left =
+            proportional(last_slot - clock.slot, elapsed_seconds, elapsed_slots)?;
+        msg!(
+            "Estimated {} seconds to the epoch end",
+            estimated_seconds_left
+        );
+        Ok(estimated_seconds_left <= self.seconds_for_stake_delta)
+    }
+
+    pub fn check_stake_delta_window(
+        &self,
+        clock: &Clock,
+        epoch_schedule: &EpochSchedule,
+    ) -> Result<()> {
+        require!(
+            self.is_in_stake_delta_window(clock, epoch_schedule)?,
+            MarinadeError::TooEarlyForStakeDelta
+        );
+        Ok(())
+    }
+
+    pub fn check_no_pending_scores_due(&self, clock: &Clock) -> Result<()> {
+        if self.pending_scores_count > 0 && clock.epoch >= self.pending_scores_epoch {
+            msg!(
+                "{} validator scores must be promoted first",
+                self.pending_scores_count
+            );
+            return err!(MarinadeError::PendingScoresNotPromoted);
+        }
+        Ok(())
+    }
+
+    pub fn on_stake_moved(&mut self, amount: u64, clock: &Clock) -> Result<()> {
+        self.on_stake_moved_uncapped(amount, clock);
         require_lte!(
             self.stake_moved,
             self.max_stake_moved_per_epoch
@@ -303,4 +555,253 @@ impl State {
         );
         Ok(())
     }
+
+    /// accounts the moved stake without checking max_stake_moved_per_epoch,
+    /// so the override still counts against the limit of later moves in the epoch
+    pub fn on_stake_moved_uncapped(&mut self, amount: u64, clock: &Clock) {
+        if clock.epoch != self.last_stake_move_epoch {
+            self.last_stake_move_epoch = clock.epoch;
+            self.stake_moved = 0;
+        }
+        self.stake_moved += amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::{AccountInfo, Clock, EpochSchedule, Pubkey};
+    use anchor_lang::solana_program::system_program;
+    use std::mem::MaybeUninit;
+
+    use super::{validator_system::ValidatorRecord, Fee, State};
+
+    fn test_state() -> State {
+        let mut state = unsafe { MaybeUninit::<State>::zeroed().assume_init() };
+        state.validator_system.total_active_balance = 1_000_000;
+        state
+    }
+
+    #[test]
+    fn test_validator_stake_cap() {
+        let mut state = test_state();
+        let mut validator = ValidatorRecord::default();
+        // 0 = disabled, as it is for states created before the field
+        assert_eq!(state.validator_stake_cap(&validator), std::u64::MAX);
+        validator.stake_cap = 5_000;
+        assert_eq!(state.validator_stake_cap(&validator), 5_000);
+
+        state.max_validator_stake_share = Fee::from_basis_points(100); // 1%
+        assert_eq!(state.validator_stake_cap(&validator), 5_000);
+        validator.stake_cap = 50_000;
+        assert_eq!(state.validator_stake_cap(&validator), 10_000);
+        validator.stake_cap = 0;
+        assert_eq!(state.validator_stake_cap(&validator), 10_000);
+    }
+
+    #[test]
+    fn test_ineligible_validator_releases_capped_stake() -> anchor_lang::Result<()> {
+        let mut state = test_state();
+        let mut validator = ValidatorRecord {
+            score: 1,
+            stake_cap: 300,
+            ..Default::default()
+        };
+        state.validator_system.total_validator_score = 2;
+        state.update_validator_capped_stake(&mut validator, 1_000)?;
+        assert_eq!(state.total_capped_stake, 300);
+        assert_eq!(state.validator_system.total_validator_score, 1);
+
+        state.update_validator_eligibility(&mut validator, |validator| validator.delinquent = true);
+        assert_eq!(validator.capped_stake, 0);
+        assert_eq!(state.total_capped_stake, 0);
+        assert_eq!(state.validator_system.total_validator_score, 1);
+        assert_eq!(state.validator_stake_target(&validator, 1_000)?, 0);
+
+        // eligible again, uncapped until check_validator_stake_cap
+        state
+            .update_validator_eligibility(&mut validator, |validator| validator.delinquent = false);
+        assert_eq!(state.total_capped_stake, 0);
+        assert_eq!(state.validator_system.total_validator_score, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validator_stake_bounds() {
+        let mut state = test_state();
+        // 0 = no band
+        assert_eq!(state.validator_stake_lower_bound(1_000), 1_000);
+        assert_eq!(state.validator_stake_upper_bound(1_000), 1_000);
+        state.stake_target_hysteresis = Fee::from_basis_points(1_000); // 10%
+        assert_eq!(state.validator_stake_lower_bound(1_000), 900);
+        assert_eq!(state.validator_stake_upper_bound(1_000), 1_100);
+        assert_eq!(state.validator_stake_lower_bound(0), 0);
+        assert_eq!(
+            state.validator_stake_upper_bound(std::u64::MAX),
+            std::u64::MAX
+        );
+        // the band does not hold back the stake needed for the tickets
+        assert_eq!(state.validator_unstake_bound(1_000, -50), 1_000);
+        assert_eq!(state.validator_unstake_bound(1_000, 0), 1_100);
+    }
+
+    #[test]
+    fn test_is_in_stake_delta_window() -> anchor_lang::Result<()> {
+        let mut state = test_state();
+        state.stake_system.slots_for_stake_delta = 3_000;
+        let epoch_schedule = EpochSchedule::without_warmup();
+        let first_slot = epoch_schedule.get_first_slot_in_epoch(10);
+        let last_slot = epoch_schedule.get_last_slot_in_epoch(10);
+        let clock_at = |slot: u64, unix_timestamp: i64| Clock {
+            slot,
+            epoch: 10,
+            epoch_start_timestamp: 1_000_000,
+            unix_timestamp,
+            ..Default::default()
+        };
+        assert!(!state.is_in_stake_delta_window(&clock_at(last_slot - 3_001, 0), &epoch_schedule)?);
+        assert!(state.is_in_stake_delta_window(&clock_at(last_slot - 3_000, 0), &epoch_schedule)?);
+
+        // one slot per second so far: 1_000 slots left are estimated as 1_000 seconds
+        state.seconds_for_stake_delta = 1_000;
+        let slot = last_slot - 1_000;
+        let elapsed = (slot - first_slot) as i64;
+        assert!(state
+            .is_in_stake_delta_window(&clock_at(slot, 1_000_000 + elapsed), &epoch_schedule)?);
+        // slots twice slower than a second: 2_000 seconds left
+        assert!(!state
+            .is_in_stake_delta_window(&clock_at(slot, 1_000_000 + 2 * elapsed), &epoch_schedule)?);
+        // no slot rate at the epoch start
+        assert!(!state.is_in_stake_delta_window(&clock_at(first_slot, 1_000_000), &epoch_schedule)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_delinquent() {
+        let mut state = test_state();
+        let clock = Clock {
+            slot: 1_000,
+            ..Default::default()
+        };
+        // 0 = disabled
+        assert!(!state.is_delinquent(None, &clock));
+        state.max_delinquent_slots = 100;
+        assert!(!state.is_delinquent(Some(900), &clock));
+        assert!(state.is_delinquent(Some(899), &clock));
+
+        assert!(state.is_delinquent(None, &clock));
+        // votes can land in a slot after the clock slot of the tx
+        assert!(!state.is_delinquent(Some(1_001), &clock));
+
+        // a closed vote account is delinquent
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let closed_vote = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &system_program::ID,
+            false,
+            0,
+        );
+        assert!(state.is_vote_account_delinquent(&closed_vote, &clock));
+        state.max_delinquent_slots = 0;
+        assert!(!state.is_vote_account_delinquent(&closed_vote, &clock));
+    }
+
+    #[test]
+    fn test_is_commission_exceeded() {
+        let mut state = test_state();
+        // 0 = disabled, as it is for states created before the field
+        assert!(!state.is_commission_exceeded(100));
+        state.max_validator_commission = 10;
+        assert!(!state.is_commission_exceeded(10));
+        assert!(state.is_commission_exceeded(11));
+    }
+
+    #[test]
+    fn test_is_oversized_stake() {
+        let mut state = test_state();
+        state.stake_system.min_stake = 10;
+        // 0 = disabled
+        assert!(!state.is_oversized_stake(std::u64::MAX / 2));
+
+        // split_oversized_stake splits max_stake_account_size off while the stake is oversized
+        state.max_stake_account_size = 100;
+        let mut stake = 355;
+        let mut splits = vec![];
+        while state.is_oversized_stake(stake) {
+            stake -= state.max_stake_account_size;
+            splits.push(state.max_stake_account_size);
+        }
+        assert_eq!(splits, vec![100, 100, 100]);
+        assert_eq!(stake, 55);
+        // the remainder keeps min_stake
+        assert!(state.is_oversized_stake(110));
+        assert!(!state.is_oversized_stake(109));
+    }
+
+    #[test]
+    fn test_on_stake_moved() {
+        let mut state = test_state();
+        // 10% of the 1_000_000 under control
+        state.max_stake_moved_per_epoch = Fee::from_basis_points(1_000);
+        let clock = Clock {
+            epoch: 5,
+            ..Default::default()
+        };
+        assert!(state.on_stake_moved(60_000, &clock).is_ok());
+        assert!(state.on_stake_moved(50_000, &clock).is_err());
+        // the override of emergency_unstake_validator is counted against the next moves
+        state.stake_moved = 0;
+        state.on_stake_moved_uncapped(150_000, &clock);
+        assert_eq!(state.stake_moved, 150_000);
+        assert!(state.on_stake_moved(1, &clock).is_err());
+        // reset in the next epoch
+        let next_epoch = Clock {
+            epoch: 6,
+            ..Default::default()
+        };
+        assert!(state.on_stake_moved(100_000, &next_epoch).is_ok());
+        assert_eq!(state.stake_moved, 100_000);
+    }
+
+    #[test]
+    fn test_capped_stake_is_redistributed() -> anchor_lang::Result<()> {
+        const TOTAL_STAKE_TARGET: u64 = 1_000;
+        let mut state = test_state();
+        let mut validators = [1, 1, 2].map(|score| ValidatorRecord {
+            score,
+            ..Default::default()
+        });
+        validators[2].stake_cap = 300;
+        state.validator_system.total_validator_score = 4;
+
+        // one pass over the list is not enough, capping raises the share of the others
+        for _ in 0..2 {
+            for validator in validators.iter_mut() {
+                state.update_validator_capped_stake(validator, TOTAL_STAKE_TARGET)?;
+            }
+        }
+        assert_eq!(state.total_capped_stake, 300);
+        assert_eq!(state.validator_system.total_validator_score, 2);
+        let targets = validators
+            .iter()
+            .map(|validator| state.validator_stake_target(validator, TOTAL_STAKE_TARGET))
+            .collect::<anchor_lang::Result<Vec<u64>>>()?;
+        assert_eq!(targets, vec![350, 350, 300]);
+
+        // uncapped when the cap is raised
+        validators[2].stake_cap = 0;
+        state.update_validator_capped_stake(&mut validators[2], TOTAL_STAKE_TARGET)?;
+        assert_eq!(state.total_capped_stake, 0);
+        assert_eq!(state.validator_system.total_validator_score, 4);
+        assert_eq!(
+            state.validator_stake_target(&validators[2], TOTAL_STAKE_TARGET)?,
+            500
+        );
+        Ok(())
+    }
 }
@@ -12,6 +12,10 @@ pub enum StakeStatus {
     Unknown,
     Active,
     Deactivating,
+    // source of a native redelegate, deactivating with its delegated lamports already moved
+    Redelegating,
+    // delegated in the current epoch or still warming up, promoted to Active by update_active
+    Activating,
 }
 
 impl Default for StakeStatus {
@@ -20,12 +24,21 @@ impl Default for StakeStatus {
     }
 }
 
+impl StakeStatus {
+    /// delegated and not deactivated by us, the stake can be deactivated
+    pub fn is_active_or_activating(&self) -> bool {
+        matches!(self, StakeStatus::Active | StakeStatus::Activating)
+    }
+}
+
 impl Display for StakeStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             StakeStatus::Unknown => write!(f, "Unknown"),
             StakeStatus::Active => write!(f, "Active"),
             StakeStatus::Deactivating => write!(f, "Deactivating"),
+            StakeStatus::Redelegating => write!(f, "Redelegating"),
+            StakeStatus::Activating => write!(f, "Activating"),
         }
     }
 }
@@ -50,18 +63,14 @@ impl StakeRecord {
         delegated_lamports: u64,
         clock: &Clock,
         is_emergency_unstaking: bool,
-        is_active: bool,
+        last_update_status: StakeStatus,
     ) -> Self {
         Self {
             stake_account: *stake_account,
             last_update_delegated_lamports: delegated_lamports,
             last_update_epoch: clock.epoch,
             is_emergency_unstaking,
-            last_update_status: if is_active {
-                StakeStatus::Active
-            } else {
-                StakeStatus::Deactivating
-            },
+            last_update_status,
         }
     }
 }
@@ -196,7 +205,7 @@ impl StakeSystem {
         delegated_lamports: u64,
         clock: &Clock,
         is_emergency_unstaking: bool,
-        is_active: bool,
+        last_update_status: StakeStatus,
     ) -> Result<()> {
         self.stake_list
             .push(
@@ -206,7 +215,7 @@ impl StakeSystem {
                     delegated_lamports,
                     clock,
                     is_emergency_unstaking,
-                    is_active,
+                    last_update_status,
                 ),
             )
             .map_err(|e| e.with_account_name("stake_list"))?;
@@ -235,6 +244,30 @@ impl StakeSystem {
         Ok(stake_record)
     }
 
+    /// get the record by its stake account pubkey. The index is only a hint,
+    /// records are moved when others are removed. Returns the actual index.
+    /// A wrong hint costs a bounded scan, records past List::MAX_FIND_KEY_SCAN
+    /// are only found by their actual index (see the limits there)
+    pub fn get_by_key(
+        &self,
+        stake_list_data: &[u8],
+        index_hint: u32,
+        stake_account: &Pubkey,
+    ) -> Result<(u32, StakeRecord)> {
+        let index = if index_hint < self.stake_count()
+            && self.get(stake_list_data, index_hint)?.stake_account == *stake_account
+        {
+            index_hint
+        } else {
+            self.stake_list
+                .find_key(stake_list_data, stake_account)
+                .ok_or_else(|| {
+                    error!(MarinadeError::WrongStakeAccountOrIndex).with_account_name("stake_list")
+                })?
+        };
+        Ok((index, self.get(stake_list_data, index)?))
+    }
+
     pub fn set(&self, stake_list_data: &mut [u8], index: u32, stake: StakeRecord) -> Result<()> {
         self.stake_list
             .set(stake_list_data, index, stake)
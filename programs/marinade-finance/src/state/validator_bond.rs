@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::ID;
+
+// SOL posted by a validator when adding itself to the validator list or with init_validator_bond.
+// The lamports above rent are the bond, they cover the rewards shortfall of the validator,
+// can be slashed by the admin and withdrawn by the authority WITHDRAW_DELAY_EPOCHS after
+// requesting the withdrawal once the validator is removed from the list
+#[account]
+#[derive(Debug, Default)]
+pub struct ValidatorBond {
+    pub state: Pubkey,          // instance of marinade state this bond belongs to
+    pub validator_vote: Pubkey, // bonded validator
+    pub authority: Pubkey,      // validator identity at the moment of registration
+    pub bump: u8,
+    pub last_settled_epoch: u64, // rewards epoch of the last settle_validator_bond run
+    pub withdraw_requested_epoch: u64, // NO_WITHDRAW_REQUEST or epoch of request_validator_bond_withdrawal
+}
+
+impl ValidatorBond {
+    pub const SEED: &'static [u8] = b"validator_bond";
+    pub const NO_WITHDRAW_REQUEST: u64 = std::u64::MAX;
+    // time for the admin to slash the bond of a removed validator before it can be withdrawn
+    pub const WITHDRAW_DELAY_EPOCHS: u64 = 2;
+
+    pub fn serialized_len() -> usize {
+        Self::default().try_to_vec().unwrap().len() + 8
+    }
+
+    pub fn balance(bond: &AccountInfo, rent: &Rent) -> u64 {
+        bond.lamports()
+            .saturating_sub(rent.minimum_balance(bond.data_len()))
+    }
+
+    // the withdrawal was requested at least WITHDRAW_DELAY_EPOCHS ago
+    pub fn is_withdrawable(&self, epoch: u64) -> bool {
+        self.withdraw_requested_epoch != Self::NO_WITHDRAW_REQUEST
+            && epoch >= self.withdraw_requested_epoch + Self::WITHDRAW_DELAY_EPOCHS
+    }
+
+    pub fn find_address(state: &Pubkey, validator_vote: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[&state.to_bytes(), Self::SEED, &validator_vote.to_bytes()],
+            &ID,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balance() {
+        let rent = Rent::default();
+        let key = Pubkey::new_unique();
+        let mut data = vec![0; ValidatorBond::serialized_len()];
+        let rent_exempt = rent.minimum_balance(data.len());
+        let mut lamports = rent_exempt + 1_000;
+        let bond = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &ID, false, 0);
+        assert_eq!(ValidatorBond::balance(&bond, &rent), 1_000);
+        **bond.try_borrow_mut_lamports().unwrap() = rent_exempt - 1;
+        assert_eq!(ValidatorBond::balance(&bond, &rent), 0);
+    }
+
+    #[test]
+    fn test_is_withdrawable() {
+        let mut bond = ValidatorBond {
+            withdraw_requested_epoch: ValidatorBond::NO_WITHDRAW_REQUEST,
+            ..Default::default()
+        };
+        assert!(!bond.is_withdrawable(std::u64::MAX));
+        bond.withdraw_requested_epoch = 10;
+        assert!(!bond.is_withdrawable(11));
+        assert!(bond.is_withdrawable(12));
+    }
+}
@@ -5,6 +5,9 @@ use anchor_lang::{prelude::*, Discriminator};
 
 use super::list::List;
 
+/// Fields after delinquent_upgrader_active_balance are stored in the additional_record_space
+/// of the list. Lists with a smaller record size must be migrated with resize_validator_records,
+/// the fields of migrated records are zero (nothing observed, no cap, nothing scheduled, not migrated)
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub struct ValidatorRecord {
     /// Validator vote pubkey
@@ -16,10 +19,44 @@ pub struct ValidatorRecord {
     pub last_stake_delta_epoch: u64,
     pub duplication_flag_bump_seed: u8,
     pub delinquent_upgrader_active_balance: u64,
+    /// delegation rewards and expected rewards observed by update_active during rewards_epoch.
+    /// The extra lamports are counted apart in epoch_extra_lamports
+    pub rewards_epoch: u64,
+    pub epoch_rewards: u64,
+    pub epoch_expected_rewards: u64,
+    /// the same counters for the previous completed epoch, used to settle the validator bond
+    pub last_rewards_epoch: u64,
+    pub last_epoch_rewards: u64,
+    pub last_epoch_expected_rewards: u64,
+    /// max lamports staked to this validator, 0 = only the global cap applies
+    pub stake_cap: u64,
+    /// set by check_validator_commission when the vote account commission is above state.max_validator_commission
+    pub commission_exceeded: bool,
+    /// set by check_validator_delinquency when the validator did not vote for more than state.max_delinquent_slots
+    pub delinquent: bool,
+    /// score scheduled by schedule_validator_scores, promoted by promote_validator_scores
+    /// once pending_score_epoch starts. pending_score_epoch = 0 means nothing is scheduled
+    pub pending_score: u32,
+    pub pending_score_epoch: u64,
+    /// set by the manager, the stakes can be drained by anyone and the validator removed by anyone once empty
+    pub marked_for_removal: bool,
+    /// new vote account of the validator set by migrate_validator, default when not migrated
+    pub migrated_to: Pubkey,
+    /// extra lamports (MEV/tips) observed during rewards_epoch and the previous epoch, not part of epoch_rewards
+    pub epoch_extra_lamports: u64,
+    pub last_epoch_extra_lamports: u64,
+    /// lifetime delegation rewards and extra lamports observed by update_active, counted apart as in the epoch counters
+    pub total_rewards: u64,
+    pub total_extra_lamports: u64,
+    /// stake target of a validator capped by check_validator_stake_cap, 0 when not capped.
+    /// The score of a capped validator is not part of total_validator_score
+    pub capped_stake: u64,
 }
 
 impl ValidatorRecord {
     pub const DUPLICATE_FLAG_SEED: &'static [u8] = b"unique_validator";
+    /// borsh size of the record, the list item_size can't be lower
+    pub const SERIALIZED_LEN: u32 = 204;
 
     pub fn find_duplication_flag(state: &Pubkey, validator_account: &Pubkey) -> (Pubkey, u8) {
         Pubkey::find_program_address(
@@ -71,8 +108,66 @@ impl ValidatorRecord {
             last_stake_delta_epoch: std::u64::MAX, // never
             duplication_flag_bump_seed,
             delinquent_upgrader_active_balance: 0,
+            rewards_epoch: 0,
+            epoch_rewards: 0,
+            epoch_expected_rewards: 0,
+            last_rewards_epoch: 0,
+            last_epoch_rewards: 0,
+            last_epoch_expected_rewards: 0,
+            stake_cap: 0,
+            commission_exceeded: false,
+            delinquent: false,
+            pending_score: 0,
+            pending_score_epoch: 0,
+            marked_for_removal: false,
+            migrated_to: Pubkey::default(),
+            epoch_extra_lamports: 0,
+            last_epoch_extra_lamports: 0,
+            total_rewards: 0,
+            total_extra_lamports: 0,
+            capped_stake: 0,
         })
     }
+
+    /// validators with commission over the max, delinquent, marked for removal
+    /// or migrated to a new vote account get no stake
+    pub fn is_eligible(&self) -> bool {
+        !(self.commission_exceeded
+            || self.delinquent
+            || self.marked_for_removal
+            || self.migrated_to != Pubkey::default())
+    }
+
+    /// score used for the stake target, 0 while the validator is not eligible for stake
+    /// or capped (its target is capped_stake then)
+    pub fn effective_score(&self) -> u32 {
+        if !self.is_eligible() || self.capped_stake > 0 {
+            0
+        } else {
+            self.score
+        }
+    }
+
+    /// move the rewards counters to last_* once the epoch they were collected in is over
+    pub fn roll_rewards_epoch(&mut self, epoch: u64) {
+        if self.rewards_epoch < epoch {
+            self.last_rewards_epoch = self.rewards_epoch;
+            self.last_epoch_rewards = self.epoch_rewards;
+            self.last_epoch_expected_rewards = self.epoch_expected_rewards;
+            self.last_epoch_extra_lamports = self.epoch_extra_lamports;
+            self.rewards_epoch = epoch;
+            self.epoch_rewards = 0;
+            self.epoch_expected_rewards = 0;
+            self.epoch_extra_lamports = 0;
+        }
+    }
+
+    /// rewards missed in the last completed rewards epoch, covered by the validator bond.
+    /// The extra lamports paid to the stakes also count as realized rewards
+    pub fn last_epoch_shortfall(&self) -> u64 {
+        self.last_epoch_expected_rewards
+            .saturating_sub(self.last_epoch_rewards + self.last_epoch_extra_lamports)
+    }
 }
 
 #[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
@@ -116,6 +211,7 @@ impl Owner for ValidatorList {
 pub struct ValidatorSystem {
     pub validator_list: List,
     pub manager_authority: Pubkey,
+    /// sum of all effective scores
     pub total_validator_score: u32,
     /// sum of all active lamports staked
     pub total_active_balance: u64,
@@ -169,6 +265,47 @@ impl ValidatorSystem {
         self.validator_list.item_size()
     }
 
+    /// lists created before the record grew must be migrated with resize_validator_records
+    fn check_record_size(&self) -> Result<()> {
+        require_gte!(
+            self.validator_record_size(),
+            ValidatorRecord::SERIALIZED_LEN,
+            MarinadeError::ValidatorRecordSizeIsTooSmall
+        );
+        Ok(())
+    }
+
+    /// the records are moved to their new place and the new bytes zeroed,
+    /// the account must already have the space for the count records of the new size
+    pub fn resize_records(
+        &mut self,
+        validator_list_data: &mut [u8],
+        record_size: u32,
+    ) -> Result<()> {
+        let old_size = self.validator_record_size() as usize;
+        require_gte!(
+            record_size as usize,
+            old_size,
+            MarinadeError::ShrinkingListWithDeletingContents
+        );
+        let count = self.validator_count() as usize;
+        let new_size = record_size as usize;
+        require_gte!(
+            validator_list_data.len(),
+            8 + count * new_size,
+            MarinadeError::ListOverflow
+        );
+        // backwards, so no record is overwritten before it is moved
+        for index in (0..count).rev() {
+            let old_start = 8 + index * old_size;
+            let new_start = 8 + index * new_size;
+            validator_list_data.copy_within(old_start..old_start + old_size, new_start);
+            validator_list_data[new_start + old_size..new_start + new_size].fill(0);
+        }
+        self.validator_list.item_size = record_size;
+        Ok(())
+    }
+
     pub fn add(
         &mut self,
         validator_list_data: &mut [u8],
@@ -177,6 +314,7 @@ impl ValidatorSystem {
         state: &Pubkey,
         duplication_flag_address: &Pubkey,
     ) -> Result<()> {
+        self.check_record_size()?;
         self.validator_list
             .push(
                 validator_list_data,
@@ -187,6 +325,20 @@ impl ValidatorSystem {
         Ok(())
     }
 
+    /// push a prepared record, its effective score is added to the total
+    pub fn add_record(
+        &mut self,
+        validator_list_data: &mut [u8],
+        validator: ValidatorRecord,
+    ) -> Result<()> {
+        self.check_record_size()?;
+        self.validator_list
+            .push(validator_list_data, validator)
+            .map_err(|e| e.with_account_name("validator_list"))?;
+        self.total_validator_score += validator.effective_score();
+        Ok(())
+    }
+
     pub fn add_with_balance(
         &mut self,
         validator_list_data: &mut [u8],
@@ -196,6 +348,7 @@ impl ValidatorSystem {
         state: &Pubkey,
         duplication_flag_address: &Pubkey,
     ) -> Result<()> {
+        self.check_record_size()?;
         let mut validator =
             ValidatorRecord::new(validator_account, score, state, duplication_flag_address)?;
         validator.active_balance = balance;
@@ -218,7 +371,7 @@ impl ValidatorSystem {
             MarinadeError::RemovingValidatorWithBalance
         );
 
-        self.total_validator_score -= record.score;
+        self.total_validator_score -= record.effective_score();
 
         self.validator_list
             .remove(validator_list_data, index)
@@ -228,6 +381,7 @@ impl ValidatorSystem {
     }
 
     pub fn get(&self, validator_list_data: &[u8], index: u32) -> Result<ValidatorRecord> {
+        self.check_record_size()?;
         self.validator_list
             .get(validator_list_data, index)
             .map_err(|e| e.with_account_name("validator_list"))
@@ -249,6 +403,31 @@ impl ValidatorSystem {
         Ok(validator_record)
     }
 
+    /// get the record by its validator vote pubkey. The index is only a hint,
+    /// records are moved when others are removed. Returns the actual index.
+    /// A wrong hint costs a bounded scan, records past List::MAX_FIND_KEY_SCAN
+    /// are only found by their actual index (see the limits there)
+    pub fn get_by_key(
+        &self,
+        validator_list_data: &[u8],
+        index_hint: u32,
+        validator_account: &Pubkey,
+    ) -> Result<(u32, ValidatorRecord)> {
+        let index = if index_hint < self.validator_count()
+            && self.get(validator_list_data, index_hint)?.validator_account == *validator_account
+        {
+            index_hint
+        } else {
+            self.validator_list
+                .find_key(validator_list_data, validator_account)
+                .ok_or_else(|| {
+                    error!(MarinadeError::WrongValidatorAccountOrIndex)
+                        .with_account_name("validator_list")
+                })?
+        };
+        Ok((index, self.get(validator_list_data, index)?))
+    }
+
     // Do not forget to update totals
     pub fn set(
         &self,
@@ -256,6 +435,7 @@ impl ValidatorSystem {
         index: u32,
         validator_record: ValidatorRecord,
     ) -> Result<()> {
+        self.check_record_size()?;
         self.validator_list
             .set(validator_list_data, index, validator_record)
             .map_err(|e| e.with_account_name("validator_list"))
@@ -271,8 +451,162 @@ impl ValidatorSystem {
         }
         proportional(
             total_stake_target,
-            validator.score as u64,
+            validator.effective_score() as u64,
             self.total_validator_score as u64,
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+
+    use super::{ValidatorList, ValidatorRecord, ValidatorSystem};
+    use crate::{error::MarinadeError, state::list::List};
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn test_record_serialized_len() {
+        assert_eq!(
+            ValidatorRecord::default().try_to_vec().unwrap().len() as u32,
+            ValidatorRecord::SERIALIZED_LEN
+        );
+    }
+
+    #[test]
+    fn test_effective_score() {
+        let validator = ValidatorRecord {
+            score: 10,
+            ..Default::default()
+        };
+        assert_eq!(validator.effective_score(), 10);
+        for not_eligible in [
+            ValidatorRecord {
+                commission_exceeded: true,
+                ..validator
+            },
+            ValidatorRecord {
+                delinquent: true,
+                ..validator
+            },
+            ValidatorRecord {
+                marked_for_removal: true,
+                ..validator
+            },
+            ValidatorRecord {
+                migrated_to: Pubkey::new_unique(),
+                ..validator
+            },
+        ] {
+            assert!(!not_eligible.is_eligible());
+            assert_eq!(not_eligible.effective_score(), 0);
+        }
+        let capped = ValidatorRecord {
+            capped_stake: 1,
+            ..validator
+        };
+        assert!(capped.is_eligible());
+        assert_eq!(capped.effective_score(), 0);
+    }
+
+    #[test]
+    fn test_roll_rewards_epoch() {
+        let mut validator = ValidatorRecord {
+            rewards_epoch: 10,
+            epoch_rewards: 100,
+            epoch_expected_rewards: 120,
+            epoch_extra_lamports: 7,
+            ..Default::default()
+        };
+        // same epoch, nothing to roll
+        validator.roll_rewards_epoch(10);
+        assert_eq!(validator.rewards_epoch, 10);
+        assert_eq!(validator.epoch_rewards, 100);
+        assert_eq!(validator.last_epoch_rewards, 0);
+
+        validator.roll_rewards_epoch(11);
+        assert_eq!(validator.last_rewards_epoch, 10);
+        assert_eq!(validator.last_epoch_rewards, 100);
+        assert_eq!(validator.last_epoch_expected_rewards, 120);
+        assert_eq!(validator.last_epoch_extra_lamports, 7);
+        assert_eq!(validator.rewards_epoch, 11);
+        assert_eq!(validator.epoch_rewards, 0);
+        assert_eq!(validator.epoch_expected_rewards, 0);
+        assert_eq!(validator.epoch_extra_lamports, 0);
+
+        // skipped epochs roll the last observed epoch
+        validator.epoch_rewards = 50;
+        validator.roll_rewards_epoch(15);
+        assert_eq!(validator.last_rewards_epoch, 11);
+        assert_eq!(validator.last_epoch_rewards, 50);
+        assert_eq!(validator.last_epoch_extra_lamports, 0);
+        assert_eq!(validator.rewards_epoch, 15);
+    }
+
+    #[test]
+    fn test_last_epoch_shortfall() {
+        let mut validator = ValidatorRecord {
+            last_epoch_expected_rewards: 120,
+            last_epoch_rewards: 100,
+            last_epoch_extra_lamports: 7,
+            ..Default::default()
+        };
+        assert_eq!(validator.last_epoch_shortfall(), 13);
+        // no shortfall when the rewards are over the expected ones
+        validator.last_epoch_extra_lamports = 30;
+        assert_eq!(validator.last_epoch_shortfall(), 0);
+        validator.last_epoch_expected_rewards = 0;
+        assert_eq!(validator.last_epoch_shortfall(), 0);
+    }
+
+    #[test]
+    fn test_resize_records() -> Result<()> {
+        const COUNT: u32 = 5;
+        // record size of lists created before the record grew
+        const OLD_RECORD_SIZE: u32 = 61;
+        let mut list_data = vec![0; (8 + COUNT * ValidatorRecord::SERIALIZED_LEN) as usize];
+        let mut validator_system = ValidatorSystem {
+            validator_list: List::new(
+                &ValidatorList::DISCRIMINATOR,
+                OLD_RECORD_SIZE,
+                Pubkey::new_unique(),
+                &mut list_data,
+            )?,
+            manager_authority: Pubkey::new_unique(),
+            total_validator_score: 0,
+            total_active_balance: 0,
+            auto_add_validator_enabled: 0,
+        };
+        let mut records = vec![];
+        for i in 0..COUNT {
+            let record = (Pubkey::new_unique(), 1_000 * i as u64, i, 7u64, 255u8, 0u64);
+            validator_system
+                .validator_list
+                .push(&mut list_data, record)?;
+            records.push(record);
+        }
+        // garbage in the unused space must not leak into the new fields
+        let used = (8 + COUNT * OLD_RECORD_SIZE) as usize;
+        list_data[used..].fill(0xff);
+        assert_eq!(
+            validator_system.get(&list_data, 0).unwrap_err(),
+            error!(MarinadeError::ValidatorRecordSizeIsTooSmall)
+        );
+
+        validator_system.resize_records(&mut list_data, ValidatorRecord::SERIALIZED_LEN)?;
+
+        assert_eq!(validator_system.validator_count(), COUNT);
+        for (i, record) in records.iter().enumerate() {
+            let validator = validator_system.get(&list_data, i as u32)?;
+            assert_eq!(validator.validator_account, record.0);
+            assert_eq!(validator.active_balance, record.1);
+            assert_eq!(validator.score, record.2);
+            assert_eq!(validator.last_stake_delta_epoch, record.3);
+            assert_eq!(validator.duplication_flag_bump_seed, record.4);
+            assert_eq!(validator.stake_cap, 0);
+            assert_eq!(validator.migrated_to, Pubkey::default());
+            assert_eq!(validator.total_rewards, 0);
+        }
+        Ok(())
+    }
+}